@@ -1,8 +1,12 @@
 use node_template_runtime::{
-    AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-    SystemConfig, TemplateAccount, TemplateModuleConfig, TransactionPaymentConfig, WASM_BINARY,
+    opaque::SessionKeys, pallet_template::RateLimit, AccountId, AccountRoles, AuraConfig,
+    BalancesConfig, CouncilConfig, GenesisConfig, GrandpaConfig, SessionConfig, Signature,
+    SystemConfig, TemplateAccount, TemplateModuleConfig, TransactionPaymentConfig,
+    ValidatorSetConfig, WASM_BINARY,
 };
+use sc_chain_spec::ChainSpecExtension;
 use sc_service::ChainType;
+use serde::{Deserialize, Serialize};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -11,8 +15,40 @@ use sp_runtime::traits::{IdentifyAccount, Verify};
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
+/// GRANDPA finality parameters. Missing values are set to defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FinalityParams {
+    /// Interval of the GRANDPA gossip messages in milliseconds.
+    pub gossip_duration: u64,
+    /// Number of blocks between generated justifications.
+    pub justification_period: u32,
+    /// Non-authority nodes don't run the GRANDPA voter and follow the imported justifications.
+    pub observer_enabled: bool,
+}
+
+impl Default for FinalityParams {
+    fn default() -> Self {
+        FinalityParams {
+            gossip_duration: 333,
+            justification_period: 512,
+            observer_enabled: false,
+        }
+    }
+}
+
+/// Node-specific chain spec extensions.
+/// Extensions are read by the node and are not a part of the runtime genesis.
+#[derive(Default, Clone, Serialize, Deserialize, ChainSpecExtension)]
+#[serde(rename_all = "camelCase")]
+pub struct Extensions {
+    /// GRANDPA finality parameters.
+    #[serde(default)]
+    pub finality: FinalityParams,
+}
+
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
-pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
+pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig, Extensions>;
 
 /// Generate a crypto pair from seed.
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
@@ -31,9 +67,17 @@ where
     AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Generate an Aura authority key.
-pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
-    (get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
+/// Generate a validator account together with its Aura and Grandpa authority keys.
+pub fn authority_keys_from_seed(s: &str) -> (AccountId, AuraId, GrandpaId) {
+    (
+        get_account_id_from_seed::<sr25519::Public>(s),
+        get_from_seed::<AuraId>(s),
+        get_from_seed::<GrandpaId>(s),
+    )
+}
+
+fn session_keys(aura: AuraId, grandpa: GrandpaId) -> SessionKeys {
+    SessionKeys { aura, grandpa }
 }
 
 pub fn development_config() -> Result<ChainSpec, String> {
@@ -51,8 +95,6 @@ pub fn development_config() -> Result<ChainSpec, String> {
                 wasm_binary,
                 // Initial PoA authorities
                 vec![authority_keys_from_seed("Alice")],
-                // Sudo account
-                get_account_id_from_seed::<sr25519::Public>("Alice"),
                 // Pre-funded accounts
                 vec![
                     get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -72,7 +114,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
         // Properties
         None,
         // Extensions
-        None,
+        Default::default(),
     ))
 }
 
@@ -94,8 +136,6 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
                     authority_keys_from_seed("Alice"),
                     authority_keys_from_seed("Bob"),
                 ],
-                // Sudo account
-                get_account_id_from_seed::<sr25519::Public>("Alice"),
                 // Pre-funded accounts
                 vec![
                     get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -123,18 +163,20 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
         // Properties
         None,
         // Extensions
-        None,
+        Default::default(),
     ))
 }
 
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
     wasm_binary: &[u8],
-    initial_authorities: Vec<(AuraId, GrandpaId)>,
-    root_key: AccountId,
+    initial_authorities: Vec<(AccountId, AuraId, GrandpaId)>,
     endowed_accounts: Vec<AccountId>,
     _enable_println: bool,
 ) -> GenesisConfig {
+    // The first endowed account is the network admin.
+    let initial_admins: Vec<AccountId> = endowed_accounts.iter().take(1).cloned().collect();
+
     GenesisConfig {
         frame_system: Some(SystemConfig {
             // Add Wasm runtime to storage.
@@ -149,26 +191,63 @@ fn testnet_genesis(
                 .map(|k| (k, 1 << 60))
                 .collect(),
         }),
+        // Aura and Grandpa authorities are set by the session pallet.
         pallet_aura: Some(AuraConfig {
-            authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
+            authorities: vec![],
         }),
         pallet_grandpa: Some(GrandpaConfig {
-            authorities: initial_authorities
+            authorities: vec![],
+        }),
+        pallet_validator_set: Some(ValidatorSetConfig {
+            validators: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+        }),
+        pallet_session: Some(SessionConfig {
+            keys: initial_authorities
                 .iter()
-                .map(|x| (x.1.clone(), 1))
+                .map(|x| {
+                    (
+                        x.0.clone(),
+                        x.0.clone(),
+                        session_keys(x.1.clone(), x.2.clone()),
+                    )
+                })
                 .collect(),
         }),
-        pallet_sudo: Some(SudoConfig {
-            // Assign network admin rights.
-            key: root_key,
+        // Registry admins form the council having root permissions.
+        pallet_collective_Instance1: Some(CouncilConfig {
+            members: initial_admins.clone(),
+            phantom: Default::default(),
         }),
         pallet_template: Some(TemplateModuleConfig {
             // set roles for each pre-set accounts (set role)
-            genesis_account_registry: endowed_accounts
-                .first()
-                .map(|acc| (acc.clone(), TemplateAccount::new_admin()))
+            genesis_account_registry: initial_admins
                 .into_iter()
+                .map(|acc| (acc, TemplateAccount::new_admin()))
                 .collect(),
+            // free calls per 100 blocks, the widest limit of the account roles applies
+            rate_limits: vec![
+                (
+                    AccountRoles::empty(),
+                    RateLimit {
+                        calls: 10,
+                        period: 100,
+                    },
+                ),
+                (
+                    AccountRoles::USER,
+                    RateLimit {
+                        calls: 100,
+                        period: 100,
+                    },
+                ),
+                (
+                    AccountRoles::ADMIN,
+                    RateLimit {
+                        calls: 1000,
+                        period: 100,
+                    },
+                ),
+            ],
         }),
         pallet_transaction_payment: Some(TransactionPaymentConfig {}),
     }
@@ -1,4 +1,5 @@
 use sc_cli::RunCmd;
+use std::str::FromStr;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -8,6 +9,39 @@ pub struct Cli {
 
     #[structopt(flatten)]
     pub run: RunCmd,
+
+    /// Block authoring mode. `instant` and `manual` modes are intended for development only.
+    #[structopt(long, default_value = "aura", possible_values = &["aura", "instant", "manual"])]
+    pub sealing: Sealing,
+
+    /// Index `pallet_template` events into the local database
+    /// and serve them by `templateModule_accountHistory` RPC.
+    #[structopt(long)]
+    pub index_events: bool,
+}
+
+/// Block authoring mode.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Sealing {
+    /// Aura slots with GRANDPA finality.
+    Aura,
+    /// Seal and finalize a block as soon as a transaction gets into the pool.
+    Instant,
+    /// Seal and finalize blocks by `engine_createBlock` and `engine_finalizeBlock` RPC calls.
+    Manual,
+}
+
+impl FromStr for Sealing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "aura" => Sealing::Aura,
+            "instant" => Sealing::Instant,
+            "manual" => Sealing::Manual,
+            _ => return Err(format!("Unknown sealing mode: {}", s)),
+        })
+    }
 }
 
 #[derive(Debug, StructOpt)]
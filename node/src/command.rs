@@ -64,6 +64,8 @@ impl SubstrateCli for Cli {
 /// Parse and run command line arguments
 pub fn run() -> sc_cli::Result<()> {
     let cli = Cli::from_args();
+    let sealing = cli.sealing;
+    let index_events = cli.index_events;
 
     match &cli.subcommand {
         Some(Subcommand::BuildSpec(cmd)) => {
@@ -78,7 +80,7 @@ pub fn run() -> sc_cli::Result<()> {
                     task_manager,
                     import_queue,
                     ..
-                } = service::new_partial(&config)?;
+                } = service::new_partial(&config, sealing)?;
                 Ok((cmd.run(client, import_queue), task_manager))
             })
         }
@@ -89,7 +91,7 @@ pub fn run() -> sc_cli::Result<()> {
                     client,
                     task_manager,
                     ..
-                } = service::new_partial(&config)?;
+                } = service::new_partial(&config, sealing)?;
                 Ok((cmd.run(client, config.database), task_manager))
             })
         }
@@ -100,7 +102,7 @@ pub fn run() -> sc_cli::Result<()> {
                     client,
                     task_manager,
                     ..
-                } = service::new_partial(&config)?;
+                } = service::new_partial(&config, sealing)?;
                 Ok((cmd.run(client, config.chain_spec), task_manager))
             })
         }
@@ -112,7 +114,7 @@ pub fn run() -> sc_cli::Result<()> {
                     task_manager,
                     import_queue,
                     ..
-                } = service::new_partial(&config)?;
+                } = service::new_partial(&config, sealing)?;
                 Ok((cmd.run(client, import_queue), task_manager))
             })
         }
@@ -128,7 +130,7 @@ pub fn run() -> sc_cli::Result<()> {
                     task_manager,
                     backend,
                     ..
-                } = service::new_partial(&config)?;
+                } = service::new_partial(&config, sealing)?;
                 Ok((cmd.run(client, backend), task_manager))
             })
         }
@@ -147,7 +149,7 @@ pub fn run() -> sc_cli::Result<()> {
             let runner = cli.create_runner(&cli.run)?;
             runner.run_node_until_exit(|config| match config.role {
                 Role::Light => service::new_light(config),
-                _ => service::new_full(config),
+                _ => service::new_full(config, sealing, index_events),
             })
         }
     }
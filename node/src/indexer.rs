@@ -0,0 +1,630 @@
+//! Node-side indexer of `pallet_template` events.
+//!
+//! Registry events of the finalized blocks are stored in a local RocksDB database keyed by
+//! account and block, so the accounts history can be queried without an archive node.
+//! Finalized blocks are never retracted, so the history has no events of abandoned forks.
+//! A new index starts from the finalized block. The last indexed block is stored as well,
+//! blocks finalized while the node was stopped are indexed on start, the ones with already
+//! pruned state are skipped.
+
+use codec::{Decode, Encode};
+use frame_system::EventRecord;
+use futures::StreamExt;
+use kvdb::KeyValueDB;
+use node_template_runtime::{
+    opaque::Block, pallet_template, AccountId, Balance, BlockNumber, Event, Hash,
+};
+use sc_client_api::{Backend, BlockchainEvents, StateBackend, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, twox_128};
+use sp_runtime::{
+    generic::BlockId,
+    traits::{BlakeTwo256, Header as _},
+};
+use std::{io, path::Path, sync::Arc};
+
+const COLUMN: u32 = 0;
+/// Key of the last indexed block number, shorter than the keys of the records.
+const LAST_INDEXED_KEY: &[u8] = b"last_indexed";
+
+/// `pallet_template` event related to the registry accounts.
+#[derive(Encode, Decode, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum RegistryEvent {
+    /// `who` has created `account` with `role`.
+    AccountCreated {
+        /// Admin account.
+        who: AccountId,
+        /// Created account.
+        account: AccountId,
+        /// Assigned role.
+        role: u8,
+    },
+    /// `who` has disabled `account`.
+    AccountDisabled {
+        /// Admin account.
+        who: AccountId,
+        /// Disabled account.
+        account: AccountId,
+    },
+    /// `balance` of `who` has been locked.
+    BalanceLocked {
+        /// Account with the locked balance.
+        who: AccountId,
+        /// Locked amount.
+        balance: Balance,
+    },
+    /// `account` has been flagged as stale by the offchain worker.
+    AccountFlagged {
+        /// Stale account.
+        account: AccountId,
+    },
+    /// `who` has set metadata of `account`.
+    MetadataSet {
+        /// Depositor of the metadata.
+        who: AccountId,
+        /// Described account.
+        account: AccountId,
+        /// Reserved deposit.
+        deposit: Balance,
+    },
+    /// Metadata of `account` has been removed.
+    MetadataCleared {
+        /// Described account.
+        account: AccountId,
+        /// Refunded depositor.
+        depositor: AccountId,
+        /// Refunded deposit.
+        deposit: Balance,
+    },
+    /// `who` has requested `role`.
+    RoleRequested {
+        /// Requesting account.
+        who: AccountId,
+        /// Requested role.
+        role: u8,
+        /// Reserved deposit.
+        deposit: Balance,
+    },
+    /// `who` has approved the role request of `account`.
+    RoleRequestApproved {
+        /// Admin account.
+        who: AccountId,
+        /// Requesting account.
+        account: AccountId,
+        /// Granted role.
+        role: u8,
+    },
+    /// `who` has rejected the role request of `account`.
+    RoleRequestRejected {
+        /// Admin account.
+        who: AccountId,
+        /// Requesting account.
+        account: AccountId,
+        /// Slashed deposit.
+        deposit: Balance,
+    },
+    /// `account` has been created by the admin origin.
+    AccountForceCreated {
+        /// Created account.
+        account: AccountId,
+        /// Assigned role.
+        role: u8,
+    },
+    /// `account` has been disabled by the admin origin.
+    AccountForceDisabled {
+        /// Disabled account.
+        account: AccountId,
+    },
+    /// All the registry locks of `account` have been removed by the admin origin.
+    BalanceUnlocked {
+        /// Unlocked account.
+        account: AccountId,
+    },
+    /// `account` has been reaped by the balances pallet.
+    AccountReaped {
+        /// Reaped account.
+        account: AccountId,
+        /// Whether the registry entry is kept.
+        kept: bool,
+    },
+    /// `admin` has delegated `permissions` to `delegate`.
+    DelegateAdded {
+        /// Delegating admin.
+        admin: AccountId,
+        /// Hot key of the admin.
+        delegate: AccountId,
+        /// Delegated permissions.
+        permissions: u8,
+        /// Last block of the delegation.
+        expiry: BlockNumber,
+    },
+    /// Delegation of `admin` to `delegate` has been removed.
+    DelegateRemoved {
+        /// Delegating admin.
+        admin: AccountId,
+        /// Hot key of the admin.
+        delegate: AccountId,
+    },
+    /// `delegate` has acted on behalf of `admin`.
+    DelegateActed {
+        /// Delegating admin.
+        admin: AccountId,
+        /// Hot key of the admin.
+        delegate: AccountId,
+    },
+    /// `sponsor` has reserved the deposit for `account`.
+    DepositReserved {
+        /// Sponsoring admin.
+        sponsor: AccountId,
+        /// Registered account.
+        account: AccountId,
+        /// Reserved deposit.
+        amount: Balance,
+    },
+    /// Deposit for `account` has been refunded to `sponsor`.
+    DepositUnreserved {
+        /// Sponsoring admin.
+        sponsor: AccountId,
+        /// Registered account.
+        account: AccountId,
+        /// Refunded deposit.
+        amount: Balance,
+    },
+    /// `who` has locked `amount` of `account` under the category `id`.
+    LockSet {
+        /// Admin account.
+        who: AccountId,
+        /// Account with the locked balance.
+        account: AccountId,
+        /// Lock category.
+        id: [u8; 8],
+        /// Locked amount.
+        amount: Balance,
+    },
+    /// `who` has removed the lock of the category `id` from `account`.
+    LockRemoved {
+        /// Admin account.
+        who: AccountId,
+        /// Unlocked account.
+        account: AccountId,
+        /// Lock category.
+        id: [u8; 8],
+    },
+    /// `creator` has reserved `amount` for `counterparty`.
+    EscrowCreated {
+        /// Escrow identifier.
+        id: u64,
+        /// Account the funds are reserved from.
+        creator: AccountId,
+        /// Receiver of the funds.
+        counterparty: AccountId,
+        /// Escrowed amount.
+        amount: Balance,
+    },
+    /// `who` has approved the release of the escrow.
+    EscrowApproved {
+        /// Escrow identifier.
+        id: u64,
+        /// Approving party.
+        who: AccountId,
+    },
+    /// Escrowed `amount` has been transferred to `counterparty`.
+    EscrowReleased {
+        /// Escrow identifier.
+        id: u64,
+        /// Receiver of the funds.
+        counterparty: AccountId,
+        /// Released amount.
+        amount: Balance,
+    },
+    /// `who` has cancelled the escrow.
+    EscrowCancelled {
+        /// Escrow identifier.
+        id: u64,
+        /// Cancelling account.
+        who: AccountId,
+    },
+}
+
+impl RegistryEvent {
+    fn from_runtime(event: Event) -> Option<Self> {
+        use pallet_template::RawEvent;
+
+        let event = match event {
+            Event::pallet_template(event) => event,
+            _ => return None,
+        };
+        Some(match event {
+            RawEvent::AccountCreated(who, account, role) => RegistryEvent::AccountCreated {
+                who,
+                account,
+                role: role.bits(),
+            },
+            RawEvent::AccountDisabled(who, account) => {
+                RegistryEvent::AccountDisabled { who, account }
+            }
+            RawEvent::BalanceLocked(who, balance) => RegistryEvent::BalanceLocked { who, balance },
+            RawEvent::AccountFlagged(account) => RegistryEvent::AccountFlagged { account },
+            RawEvent::MetadataSet(who, account, deposit) => RegistryEvent::MetadataSet {
+                who,
+                account,
+                deposit,
+            },
+            RawEvent::MetadataCleared(account, depositor, deposit) => {
+                RegistryEvent::MetadataCleared {
+                    account,
+                    depositor,
+                    deposit,
+                }
+            }
+            RawEvent::RoleRequested(who, role, deposit) => RegistryEvent::RoleRequested {
+                who,
+                role: role.bits(),
+                deposit,
+            },
+            RawEvent::RoleRequestApproved(who, account, role) => {
+                RegistryEvent::RoleRequestApproved {
+                    who,
+                    account,
+                    role: role.bits(),
+                }
+            }
+            RawEvent::RoleRequestRejected(who, account, deposit) => {
+                RegistryEvent::RoleRequestRejected {
+                    who,
+                    account,
+                    deposit,
+                }
+            }
+            RawEvent::AccountForceCreated(account, role) => RegistryEvent::AccountForceCreated {
+                account,
+                role: role.bits(),
+            },
+            RawEvent::AccountForceDisabled(account) => {
+                RegistryEvent::AccountForceDisabled { account }
+            }
+            RawEvent::BalanceUnlocked(account) => RegistryEvent::BalanceUnlocked { account },
+            RawEvent::AccountReaped(account, kept) => {
+                RegistryEvent::AccountReaped { account, kept }
+            }
+            RawEvent::DelegateAdded(admin, delegate, permissions, expiry) => {
+                RegistryEvent::DelegateAdded {
+                    admin,
+                    delegate,
+                    permissions,
+                    expiry,
+                }
+            }
+            RawEvent::DelegateRemoved(admin, delegate) => {
+                RegistryEvent::DelegateRemoved { admin, delegate }
+            }
+            RawEvent::DelegateActed(admin, delegate) => {
+                RegistryEvent::DelegateActed { admin, delegate }
+            }
+            RawEvent::DepositReserved(sponsor, account, amount) => RegistryEvent::DepositReserved {
+                sponsor,
+                account,
+                amount,
+            },
+            RawEvent::DepositUnreserved(sponsor, account, amount) => {
+                RegistryEvent::DepositUnreserved {
+                    sponsor,
+                    account,
+                    amount,
+                }
+            }
+            RawEvent::LockSet(who, account, id, amount) => RegistryEvent::LockSet {
+                who,
+                account,
+                id,
+                amount,
+            },
+            RawEvent::LockRemoved(who, account, id) => {
+                RegistryEvent::LockRemoved { who, account, id }
+            }
+            RawEvent::EscrowCreated(id, creator, counterparty, amount) => {
+                RegistryEvent::EscrowCreated {
+                    id,
+                    creator,
+                    counterparty,
+                    amount,
+                }
+            }
+            RawEvent::EscrowApproved(id, who) => RegistryEvent::EscrowApproved { id, who },
+            RawEvent::EscrowReleased(id, counterparty, amount) => RegistryEvent::EscrowReleased {
+                id,
+                counterparty,
+                amount,
+            },
+            RawEvent::EscrowCancelled(id, who) => RegistryEvent::EscrowCancelled { id, who },
+            RawEvent::SomethingStored(..)
+            | RawEvent::RootDispatched(..)
+            | RawEvent::ConfigUpdated(..)
+            | RawEvent::ConfigWritersSet(..)
+            | RawEvent::RateLimitSet(..) => return None,
+        })
+    }
+
+    /// Name of the event, the same as the serialized `type` field.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RegistryEvent::AccountCreated { .. } => "accountCreated",
+            RegistryEvent::AccountDisabled { .. } => "accountDisabled",
+            RegistryEvent::BalanceLocked { .. } => "balanceLocked",
+            RegistryEvent::AccountFlagged { .. } => "accountFlagged",
+            RegistryEvent::MetadataSet { .. } => "metadataSet",
+            RegistryEvent::MetadataCleared { .. } => "metadataCleared",
+            RegistryEvent::RoleRequested { .. } => "roleRequested",
+            RegistryEvent::RoleRequestApproved { .. } => "roleRequestApproved",
+            RegistryEvent::RoleRequestRejected { .. } => "roleRequestRejected",
+            RegistryEvent::AccountForceCreated { .. } => "accountForceCreated",
+            RegistryEvent::AccountForceDisabled { .. } => "accountForceDisabled",
+            RegistryEvent::BalanceUnlocked { .. } => "balanceUnlocked",
+            RegistryEvent::AccountReaped { .. } => "accountReaped",
+            RegistryEvent::DelegateAdded { .. } => "delegateAdded",
+            RegistryEvent::DelegateRemoved { .. } => "delegateRemoved",
+            RegistryEvent::DelegateActed { .. } => "delegateActed",
+            RegistryEvent::DepositReserved { .. } => "depositReserved",
+            RegistryEvent::DepositUnreserved { .. } => "depositUnreserved",
+            RegistryEvent::LockSet { .. } => "lockSet",
+            RegistryEvent::LockRemoved { .. } => "lockRemoved",
+            RegistryEvent::EscrowCreated { .. } => "escrowCreated",
+            RegistryEvent::EscrowApproved { .. } => "escrowApproved",
+            RegistryEvent::EscrowReleased { .. } => "escrowReleased",
+            RegistryEvent::EscrowCancelled { .. } => "escrowCancelled",
+        }
+    }
+
+    /// Names of all the event kinds, as returned by `kind`.
+    pub const KINDS: &'static [&'static str] = &[
+        "accountCreated",
+        "accountDisabled",
+        "balanceLocked",
+        "accountFlagged",
+        "metadataSet",
+        "metadataCleared",
+        "roleRequested",
+        "roleRequestApproved",
+        "roleRequestRejected",
+        "accountForceCreated",
+        "accountForceDisabled",
+        "balanceUnlocked",
+        "accountReaped",
+        "delegateAdded",
+        "delegateRemoved",
+        "delegateActed",
+        "depositReserved",
+        "depositUnreserved",
+        "lockSet",
+        "lockRemoved",
+        "escrowCreated",
+        "escrowApproved",
+        "escrowReleased",
+        "escrowCancelled",
+    ];
+
+    /// Accounts the event is indexed by.
+    pub fn accounts(&self) -> Vec<&AccountId> {
+        match self {
+            RegistryEvent::AccountCreated { who, account, .. }
+            | RegistryEvent::AccountDisabled { who, account }
+            | RegistryEvent::MetadataSet { who, account, .. }
+            | RegistryEvent::RoleRequestApproved { who, account, .. }
+            | RegistryEvent::RoleRequestRejected { who, account, .. }
+            | RegistryEvent::LockSet { who, account, .. }
+            | RegistryEvent::LockRemoved { who, account, .. } => vec![who, account],
+            RegistryEvent::MetadataCleared {
+                account, depositor, ..
+            } => vec![account, depositor],
+            RegistryEvent::DepositReserved {
+                sponsor, account, ..
+            }
+            | RegistryEvent::DepositUnreserved {
+                sponsor, account, ..
+            } => vec![sponsor, account],
+            RegistryEvent::EscrowCreated {
+                creator,
+                counterparty,
+                ..
+            } => vec![creator, counterparty],
+            RegistryEvent::EscrowApproved { who, .. }
+            | RegistryEvent::EscrowCancelled { who, .. } => vec![who],
+            RegistryEvent::EscrowReleased { counterparty, .. } => vec![counterparty],
+            RegistryEvent::DelegateAdded {
+                admin, delegate, ..
+            }
+            | RegistryEvent::DelegateRemoved { admin, delegate }
+            | RegistryEvent::DelegateActed { admin, delegate } => vec![admin, delegate],
+            RegistryEvent::BalanceLocked { who, .. } | RegistryEvent::RoleRequested { who, .. } => {
+                vec![who]
+            }
+            RegistryEvent::AccountFlagged { account }
+            | RegistryEvent::AccountForceCreated { account, .. }
+            | RegistryEvent::AccountForceDisabled { account }
+            | RegistryEvent::BalanceUnlocked { account }
+            | RegistryEvent::AccountReaped { account, .. } => vec![account],
+        }
+    }
+}
+
+/// Indexed event with its position in the chain.
+#[derive(Encode, Decode, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRecord {
+    /// Number of the block the event has been emitted in.
+    pub block_number: BlockNumber,
+    /// Hash of the block the event has been emitted in.
+    pub block_hash: Hash,
+    /// Index of the event in the block.
+    pub event_index: u32,
+    /// The event.
+    pub event: RegistryEvent,
+}
+
+/// Local database of the indexed events.
+#[derive(Clone)]
+pub struct EventIndex {
+    db: Arc<dyn KeyValueDB>,
+}
+
+impl EventIndex {
+    /// Open the database at `path`, create it if missing.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        std::fs::create_dir_all(path)?;
+        let path = path
+            .to_str()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Invalid index path"))?;
+        let config = kvdb_rocksdb::DatabaseConfig::with_columns(1);
+        let db = kvdb_rocksdb::Database::open(&config, path)?;
+
+        Ok(EventIndex { db: Arc::new(db) })
+    }
+
+    // Big endian numbers keep the records of an account ordered by block.
+    fn key(account: &AccountId, block_number: BlockNumber, event_index: u32) -> Vec<u8> {
+        let mut key = account.encode();
+        key.extend_from_slice(&block_number.to_be_bytes());
+        key.extend_from_slice(&event_index.to_be_bytes());
+        key
+    }
+
+    /// Number of the last indexed block.
+    pub fn last_indexed(&self) -> io::Result<Option<BlockNumber>> {
+        Ok(self
+            .db
+            .get(COLUMN, LAST_INDEXED_KEY)?
+            .and_then(|value| BlockNumber::decode(&mut &value[..]).ok()))
+    }
+
+    /// Store the records of the block under each related account and mark the block indexed.
+    pub fn insert(&self, block_number: BlockNumber, records: &[HistoryRecord]) -> io::Result<()> {
+        let mut tx = self.db.transaction();
+        for record in records {
+            let value = record.encode();
+            for account in record.event.accounts() {
+                let key = Self::key(account, record.block_number, record.event_index);
+                tx.put(COLUMN, &key, &value);
+            }
+        }
+        tx.put(COLUMN, LAST_INDEXED_KEY, &block_number.encode());
+        self.db.write(tx)
+    }
+
+    /// Up to `limit` records related to the account starting from `from_block`, oldest first.
+    pub fn account_history(
+        &self,
+        account: &AccountId,
+        from_block: BlockNumber,
+        limit: usize,
+    ) -> Vec<HistoryRecord> {
+        let prefix = account.encode();
+        let from = Self::key(account, from_block, 0);
+        self.db
+            .iter_with_prefix(COLUMN, &prefix)
+            .filter(|(key, _)| key[..] >= from[..])
+            .filter_map(|(_, value)| HistoryRecord::decode(&mut &value[..]).ok())
+            .take(limit)
+            .collect()
+    }
+}
+
+/// Storage key of `frame_system::Events`.
+fn events_storage_key() -> StorageKey {
+    StorageKey([twox_128(b"System"), twox_128(b"Events")].concat())
+}
+
+/// Decode `pallet_template` events of the block.
+pub fn block_records<C, B>(
+    client: &C,
+    hash: Hash,
+    block_number: BlockNumber,
+) -> Result<Vec<HistoryRecord>, String>
+where
+    C: StorageProvider<Block, B>,
+    B: Backend<Block>,
+    B::State: StateBackend<BlakeTwo256>,
+{
+    let data = match client
+        .storage(&BlockId::Hash(hash), &events_storage_key())
+        .map_err(|e| e.to_string())?
+    {
+        Some(data) => data,
+        None => return Ok(Vec::new()),
+    };
+    let events =
+        Vec::<EventRecord<Event, Hash>>::decode(&mut &data.0[..]).map_err(|e| e.to_string())?;
+
+    Ok(events
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, record)| {
+            RegistryEvent::from_runtime(record.event).map(|event| HistoryRecord {
+                block_number,
+                block_hash: hash,
+                event_index: i as u32,
+                event,
+            })
+        })
+        .collect())
+}
+
+/// Index `pallet_template` events of the finalized blocks until the node is stopped.
+pub async fn run<C, B>(client: Arc<C>, index: EventIndex)
+where
+    C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+    B: Backend<Block>,
+    B::State: StateBackend<BlakeTwo256>,
+{
+    let mut finalized = client.finality_notification_stream();
+    index_finalized(&*client, &index, client.info().finalized_number);
+    while let Some(notification) = finalized.next().await {
+        index_finalized(&*client, &index, *notification.header.number());
+    }
+}
+
+/// Index the finalized blocks following the last indexed one up to `to`, a new index starts
+/// from `to`. Client skips notifications when many blocks are finalized at once, so the gap
+/// is filled from the canonical chain. Blocks with pruned state are skipped, a failed write
+/// is retried on the next notification.
+fn index_finalized<C, B>(client: &C, index: &EventIndex, to: BlockNumber)
+where
+    C: HeaderBackend<Block> + StorageProvider<Block, B>,
+    B: Backend<Block>,
+    B::State: StateBackend<BlakeTwo256>,
+{
+    let from = match index.last_indexed() {
+        Ok(last) => last.map_or(to, |last| last + 1),
+        Err(e) => {
+            log::warn!("Failed to read the last indexed block: {}", e);
+            return;
+        }
+    };
+    for block_number in from..=to {
+        let hash = match client.hash(block_number) {
+            Ok(Some(hash)) => hash,
+            Ok(None) => {
+                log::warn!(
+                    "Failed to index events of block #{}: unknown block",
+                    block_number
+                );
+                return;
+            }
+            Err(e) => {
+                log::warn!("Failed to index events of block #{}: {}", block_number, e);
+                return;
+            }
+        };
+        // The state of the old blocks may be pruned already, the block is marked indexed
+        // anyway so the indexer doesn't get stuck on it.
+        let records = block_records(client, hash, block_number).unwrap_or_else(|e| {
+            log::warn!("Skipped events of block #{}: {}", block_number, e);
+            Vec::new()
+        });
+        if let Err(e) = index.insert(block_number, &records) {
+            log::warn!("Failed to index events of block #{}: {}", block_number, e);
+            return;
+        }
+    }
+}
@@ -1,3 +1,7 @@
 pub mod chain_spec;
+pub mod cli;
+pub mod indexer;
+pub mod light_rpc;
 pub mod rpc;
 pub mod service;
+pub mod template_rpc;
@@ -0,0 +1,183 @@
+//! RPC methods of the light client served by remote calls and storage proofs of full nodes.
+
+use crate::template_rpc::{
+    account_storage_key, decode_account, rpc_error, FutureResult, RegistryAccount,
+    TemplateStateApi, STATE_ERROR,
+};
+use codec::{Decode, Encode};
+use futures::{future::ready, TryFutureExt};
+use jsonrpc_core::futures::{future::result, Future};
+use jsonrpc_derive::rpc;
+use node_template_runtime::{opaque::Block, AccountId, Balance, Hash};
+use pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo;
+use sc_client_api::light::{
+    future_header, Fetcher, RemoteBlockchain, RemoteCallRequest, RemoteReadRequest,
+};
+use sp_blockchain::{Error as ClientError, HeaderBackend};
+use sp_core::Bytes;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+/// The call to runtime failed, as `pallet_transaction_payment_rpc` reports it.
+const RUNTIME_ERROR: i64 = 1;
+/// The transaction is not decodable, as `pallet_transaction_payment_rpc` reports it.
+const DECODE_ERROR: i64 = 2;
+
+/// `payment_queryInfo` executed by the full nodes.
+#[rpc(server)]
+pub trait LightTransactionPaymentApi<BlockHash> {
+    /// Dispatch info and partial fee of the encoded extrinsic at the given block,
+    /// the best block by default.
+    #[rpc(name = "payment_queryInfo")]
+    fn query_info(
+        &self,
+        encoded_xt: Bytes,
+        at: Option<BlockHash>,
+    ) -> FutureResult<RuntimeDispatchInfo<Balance>>;
+}
+
+/// Header of the given block, the best block by default, fetched from the full nodes
+/// if the light client doesn't have it.
+fn remote_header<C, F>(
+    client: &C,
+    remote_blockchain: &dyn RemoteBlockchain<Block>,
+    fetcher: &F,
+    at: Option<Hash>,
+) -> impl futures::Future<Output = Result<(Hash, <Block as BlockT>::Header), ClientError>>
+where
+    C: HeaderBackend<Block>,
+    F: Fetcher<Block>,
+{
+    let block = at.unwrap_or_else(|| client.info().best_hash);
+    future_header(remote_blockchain, fetcher, BlockId::Hash(block)).and_then(move |header| {
+        ready(
+            header
+                .map(|header| (block, header))
+                .ok_or_else(|| ClientError::UnknownBlock(format!("{}", block))),
+        )
+    })
+}
+
+/// Implementation of `LightTransactionPaymentApi` calling the runtime of the full nodes.
+pub struct LightTransactionPayment<C, F> {
+    client: Arc<C>,
+    remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+    fetcher: Arc<F>,
+}
+
+impl<C, F> LightTransactionPayment<C, F> {
+    /// Create new `LightTransactionPayment` with the given client and fetcher.
+    pub fn new(
+        client: Arc<C>,
+        remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+        fetcher: Arc<F>,
+    ) -> Self {
+        LightTransactionPayment {
+            client,
+            remote_blockchain,
+            fetcher,
+        }
+    }
+}
+
+impl<C, F> LightTransactionPaymentApi<Hash> for LightTransactionPayment<C, F>
+where
+    C: HeaderBackend<Block> + Send + Sync + 'static,
+    F: Fetcher<Block> + 'static,
+{
+    fn query_info(
+        &self,
+        encoded_xt: Bytes,
+        at: Option<Hash>,
+    ) -> FutureResult<RuntimeDispatchInfo<Balance>> {
+        let uxt: <Block as BlockT>::Extrinsic = match Decode::decode(&mut &*encoded_xt) {
+            Ok(uxt) => uxt,
+            Err(e) => {
+                return Box::new(result(Err(rpc_error(
+                    DECODE_ERROR,
+                    format!("Unable to query dispatch info: {}", e),
+                ))))
+            }
+        };
+        let call_data = (uxt, encoded_xt.len() as u32).encode();
+
+        let fetcher = self.fetcher.clone();
+        let future_info =
+            remote_header(&*self.client, &*self.remote_blockchain, &*self.fetcher, at)
+                .and_then(move |(block, header)| {
+                    fetcher.remote_call(RemoteCallRequest {
+                        block,
+                        header,
+                        method: "TransactionPaymentApi_query_info".into(),
+                        call_data,
+                        retry_count: None,
+                    })
+                })
+                .and_then(|info| {
+                    ready(
+                        RuntimeDispatchInfo::<Balance>::decode(&mut &info[..]).map_err(|e| {
+                            ClientError::CallResultDecode("Cannot decode dispatch info", e)
+                        }),
+                    )
+                })
+                .compat()
+                .map_err(|e| {
+                    rpc_error(
+                        RUNTIME_ERROR,
+                        format!("Unable to query dispatch info: {:?}", e),
+                    )
+                });
+
+        Box::new(future_info)
+    }
+}
+
+/// Implementation of `TemplateStateApi` reading the state of the full nodes by storage proofs.
+pub struct LightTemplateState<C, F> {
+    client: Arc<C>,
+    remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+    fetcher: Arc<F>,
+}
+
+impl<C, F> LightTemplateState<C, F> {
+    /// Create new `LightTemplateState` with the given client and fetcher.
+    pub fn new(
+        client: Arc<C>,
+        remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+        fetcher: Arc<F>,
+    ) -> Self {
+        LightTemplateState {
+            client,
+            remote_blockchain,
+            fetcher,
+        }
+    }
+}
+
+impl<C, F> TemplateStateApi<Hash> for LightTemplateState<C, F>
+where
+    C: HeaderBackend<Block> + Send + Sync + 'static,
+    F: Fetcher<Block> + 'static,
+{
+    fn account(&self, account: AccountId, at: Option<Hash>) -> FutureResult<RegistryAccount> {
+        let key = account_storage_key(&account).0;
+        let fetcher = self.fetcher.clone();
+        let future_account =
+            remote_header(&*self.client, &*self.remote_blockchain, &*self.fetcher, at)
+                .and_then(move |(block, header)| {
+                    fetcher
+                        .remote_read(RemoteReadRequest {
+                            block,
+                            header,
+                            keys: vec![key.clone()],
+                            retry_count: None,
+                        })
+                        .map_ok(move |mut values| values.remove(&key).flatten())
+                })
+                .compat()
+                .map_err(|e| rpc_error(STATE_ERROR, format!("{:?}", e)))
+                .and_then(decode_account);
+
+        Box::new(future_account)
+    }
+}
@@ -6,7 +6,10 @@ mod chain_spec;
 mod service;
 mod cli;
 mod command;
+mod indexer;
+mod light_rpc;
 mod rpc;
+mod template_rpc;
 
 fn main() -> sc_cli::Result<()> {
     command::run()
@@ -7,11 +7,14 @@
 
 use std::sync::Arc;
 
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use node_template_runtime::{opaque::Block, AccountId, Balance, Hash, Index};
+use sc_client_api::{Backend, BlockchainEvents, StateBackend, StorageProvider};
+use sc_consensus_manual_seal::EngineCommand;
 pub use sc_rpc_api::DenyUnsafe;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_runtime::traits::BlakeTwo256;
 use sp_transaction_pool::TransactionPool;
 
 /// Full client dependencies.
@@ -22,20 +25,46 @@ pub struct FullDeps<C, P> {
     pub pool: Arc<P>,
     /// Whether to deny unsafe calls
     pub deny_unsafe: DenyUnsafe,
+    /// Channel to the manual seal authorship task, if the node runs in instant or manual sealing mode.
+    pub command_sink: Option<futures::channel::mpsc::Sender<EngineCommand<Hash>>>,
+    /// Local index of `pallet_template` events, if the node runs with `--index-events`.
+    pub event_index: Option<crate::indexer::EventIndex>,
+    /// Executor of the RPC subscriptions.
+    pub subscription_executor: sc_rpc::SubscriptionTaskExecutor,
+}
+
+/// Light client extra dependencies.
+pub struct LightDeps<C, F, P> {
+    /// The client instance to use.
+    pub client: Arc<C>,
+    /// Transaction pool instance.
+    pub pool: Arc<P>,
+    /// Remote access to the blockchain (async).
+    pub remote_blockchain: Arc<dyn sc_client_api::light::RemoteBlockchain<Block>>,
+    /// Fetcher instance.
+    pub fetcher: Arc<F>,
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(deps: FullDeps<C, P>) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
+/// `B` is the client backend the events are read from.
+pub fn create_full<C, P, B>(deps: FullDeps<C, P>) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
 where
     C: ProvideRuntimeApi<Block>,
     C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+    C: BlockchainEvents<Block> + StorageProvider<Block, B>,
     C: Send + Sync + 'static,
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
     C::Api: BlockBuilder<Block>,
+    C::Api: sp_api::Metadata<Block>,
     P: TransactionPool + 'static,
+    B: Backend<Block> + Send + Sync + 'static,
+    B::State: StateBackend<BlakeTwo256>,
 {
+    use crate::template_rpc::{Template, TemplateApi, TemplateState, TemplateStateApi};
+    use jsonrpc_pubsub::manager::SubscriptionManager;
     use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+    use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApi};
     use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
     let mut io = jsonrpc_core::IoHandler::default();
@@ -43,6 +72,9 @@ where
         client,
         pool,
         deny_unsafe,
+        command_sink,
+        event_index,
+        subscription_executor,
     } = deps;
 
     io.extend_with(SystemApi::to_delegate(FullSystem::new(
@@ -52,7 +84,23 @@ where
     )));
 
     io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
+        client.clone(),
+    )));
+
+    if let Some(command_sink) = command_sink {
+        // `engine_createBlock` and `engine_finalizeBlock` send commands
+        // to the background authorship task.
+        io.extend_with(ManualSealApi::to_delegate(ManualSeal::new(command_sink)));
+    }
+
+    io.extend_with(TemplateStateApi::to_delegate(TemplateState::<_, B>::new(
+        client.clone(),
+    )));
+
+    io.extend_with(TemplateApi::to_delegate(Template::<_, B>::new(
         client,
+        event_index,
+        SubscriptionManager::new(Arc::new(subscription_executor)),
     )));
 
     // Extend this RPC with a custom API by using the following syntax.
@@ -62,3 +110,53 @@ where
 
     io
 }
+
+/// Instantiate all light RPC extensions.
+/// Light nodes don't have the state, so the methods are served by remote calls and
+/// storage proofs of the full nodes.
+pub fn create_light<C, P, F>(deps: LightDeps<C, F, P>) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
+where
+    C: HeaderBackend<Block>,
+    C: Send + Sync + 'static,
+    F: sc_client_api::light::Fetcher<Block> + 'static,
+    P: TransactionPool + 'static,
+{
+    use crate::light_rpc::{
+        LightTemplateState, LightTransactionPayment, LightTransactionPaymentApi,
+    };
+    use crate::template_rpc::TemplateStateApi;
+    use substrate_frame_rpc_system::{LightSystem, SystemApi};
+
+    let LightDeps {
+        client,
+        pool,
+        remote_blockchain,
+        fetcher,
+    } = deps;
+    let mut io = jsonrpc_core::IoHandler::default();
+
+    // `system_accountNextIndex` reads the account nonce by a remote storage proof
+    // and takes pending transactions of the local pool into account.
+    io.extend_with(SystemApi::<Hash, AccountId, Index>::to_delegate(
+        LightSystem::new(
+            client.clone(),
+            remote_blockchain.clone(),
+            fetcher.clone(),
+            pool,
+        ),
+    ));
+
+    // `payment_queryInfo` calls the runtime of a full node.
+    io.extend_with(LightTransactionPaymentApi::to_delegate(
+        LightTransactionPayment::new(client.clone(), remote_blockchain.clone(), fetcher.clone()),
+    ));
+
+    // `templateModule_account` reads the registry by a remote storage proof.
+    io.extend_with(TemplateStateApi::to_delegate(LightTemplateState::new(
+        client,
+        remote_blockchain,
+        fetcher,
+    )));
+
+    io
+}
@@ -1,17 +1,26 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::{ExecutorProvider, RemoteBackend};
+use crate::cli::Sealing;
+use codec::Decode;
+use futures::StreamExt;
+use node_template_runtime::{self, opaque::Block, RuntimeApi, SLOT_DURATION};
+use sc_client_api::{ExecutorProvider, RemoteBackend, StorageProvider};
+use sc_consensus_manual_seal::EngineCommand;
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sc_finality_grandpa::{
     FinalityProofProvider as GrandpaFinalityProofProvider, SharedVoterState,
 };
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
+use sp_blockchain::HeaderBackend;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use sp_inherents::InherentDataProviders;
+use sp_core::{storage::StorageKey, twox_128};
+use sp_inherents::{InherentData, InherentDataProviders, InherentIdentifier, ProvideInherentData};
+use sp_runtime::generic::BlockId;
+use sp_transaction_pool::TransactionPool;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Our native executor instance.
 native_executor_instance!(
@@ -24,9 +33,54 @@ native_executor_instance!(
 type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+
+/// Provide timestamps for the manually sealed blocks.
+/// Each next block is one slot later than the previous one regardless of the wall clock,
+/// so blocks can be sealed at any rate without breaking Aura and Timestamp pallets checks.
+/// Timestamps continue from `Timestamp::Now` of the best block, so the chain can be restarted,
+/// a new chain starts from the current time.
+struct MockTimestampInherentDataProvider(AtomicU64);
+
+impl MockTimestampInherentDataProvider {
+    fn new(client: &FullClient) -> Result<Self, ServiceError> {
+        let key = StorageKey([twox_128(b"Timestamp"), twox_128(b"Now")].concat());
+        let best_now = client
+            .storage(&BlockId::Hash(client.info().best_hash), &key)?
+            .and_then(|data| u64::decode(&mut &data.0[..]).ok());
+        let next = match best_now {
+            Some(now) if now != 0 => now + SLOT_DURATION,
+            _ => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Current time is always after unix epoch; qed")
+                .as_millis() as u64,
+        };
+        Ok(MockTimestampInherentDataProvider(AtomicU64::new(next)))
+    }
+}
+
+impl ProvideInherentData for MockTimestampInherentDataProvider {
+    fn inherent_identifier(&self) -> &'static InherentIdentifier {
+        &sp_timestamp::INHERENT_IDENTIFIER
+    }
+
+    fn provide_inherent_data(
+        &self,
+        inherent_data: &mut InherentData,
+    ) -> Result<(), sp_inherents::Error> {
+        let timestamp = self.0.fetch_add(SLOT_DURATION, Ordering::SeqCst);
+        inherent_data.put_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp)
+    }
+
+    fn error_to_string(&self, error: &[u8]) -> Option<String> {
+        sp_timestamp::InherentError::try_from(&sp_timestamp::INHERENT_IDENTIFIER, error)
+            .map(|e| format!("{:?}", e))
+    }
+}
+
 #[allow(clippy::type_complexity)]
 pub fn new_partial(
     config: &Configuration,
+    sealing: Sealing,
 ) -> Result<
     sc_service::PartialComponents<
         FullClient,
@@ -77,17 +131,30 @@ pub fn new_partial(
         client.clone(),
     );
 
-    let import_queue = sc_consensus_aura::import_queue::<_, _, _, AuraPair, _, _>(
-        sc_consensus_aura::slot_duration(&*client)?,
-        aura_block_import.clone(),
-        Some(Box::new(grandpa_block_import)),
-        None,
-        client.clone(),
-        inherent_data_providers.clone(),
-        &task_manager.spawn_handle(),
-        config.prometheus_registry(),
-        sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
-    )?;
+    let import_queue = match sealing {
+        Sealing::Aura => sc_consensus_aura::import_queue::<_, _, _, AuraPair, _, _>(
+            sc_consensus_aura::slot_duration(&*client)?,
+            aura_block_import.clone(),
+            Some(Box::new(grandpa_block_import)),
+            None,
+            client.clone(),
+            inherent_data_providers.clone(),
+            &task_manager.spawn_handle(),
+            config.prometheus_registry(),
+            sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
+        )?,
+        Sealing::Instant | Sealing::Manual => {
+            inherent_data_providers
+                .register_provider(MockTimestampInherentDataProvider::new(&client)?)
+                .map_err(sp_consensus::Error::InherentData)?;
+
+            sc_consensus_manual_seal::import_queue(
+                Box::new(client.clone()),
+                &task_manager.spawn_handle(),
+                config.prometheus_registry(),
+            )
+        }
+    };
 
     Ok(sc_service::PartialComponents {
         client,
@@ -103,7 +170,12 @@ pub fn new_partial(
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
+/// With `index_events` the `pallet_template` events are indexed by a background task.
+pub fn new_full(
+    config: Configuration,
+    sealing: Sealing,
+    index_events: bool,
+) -> Result<TaskManager, ServiceError> {
     let sc_service::PartialComponents {
         client,
         backend,
@@ -114,7 +186,13 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
         transaction_pool,
         inherent_data_providers,
         other: (block_import, grandpa_link),
-    } = new_partial(&config)?;
+    } = new_partial(&config, sealing)?;
+
+    let finality = sc_chain_spec::get_extension::<crate::chain_spec::FinalityParams>(
+        config.chain_spec.extensions(),
+    )
+    .cloned()
+    .unwrap_or_default();
 
     let finality_proof_provider =
         GrandpaFinalityProofProvider::new_for_service(backend.clone(), client.clone());
@@ -142,6 +220,28 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
         );
     }
 
+    let event_index = if index_events {
+        let path = config
+            .base_path
+            .as_ref()
+            .ok_or_else(|| ServiceError::Other("Base path is required to index events".into()))?
+            .path()
+            .join("chains")
+            .join(config.chain_spec.id())
+            .join("template_index");
+        let event_index = crate::indexer::EventIndex::open(&path)
+            .map_err(|e| ServiceError::Other(format!("Failed to open events index: {}", e)))?;
+
+        // The indexer reads the state and writes the index synchronously.
+        task_manager.spawn_handle().spawn_blocking(
+            "template-indexer",
+            crate::indexer::run::<_, FullBackend>(client.clone(), event_index.clone()),
+        );
+        Some(event_index)
+    } else {
+        None
+    };
+
     let role = config.role.clone();
     let force_authoring = config.force_authoring;
     let name = config.network.node_name.clone();
@@ -149,18 +249,28 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
     let prometheus_registry = config.prometheus_registry().cloned();
     let telemetry_connection_sinks = sc_service::TelemetryConnectionSinks::default();
 
+    // Channel for the rpc handler to communicate with the manual seal authorship task.
+    let (command_sink, commands_stream) = futures::channel::mpsc::channel(1000);
+
     let rpc_extensions_builder = {
         let client = client.clone();
         let pool = transaction_pool.clone();
+        let command_sink = match sealing {
+            Sealing::Aura => None,
+            Sealing::Instant | Sealing::Manual => Some(command_sink),
+        };
 
-        Box::new(move |deny_unsafe, _| {
+        Box::new(move |deny_unsafe, subscription_executor| {
             let deps = crate::rpc::FullDeps {
                 client: client.clone(),
                 pool: pool.clone(),
                 deny_unsafe,
+                command_sink: command_sink.clone(),
+                event_index: event_index.clone(),
+                subscription_executor,
             };
 
-            crate::rpc::create_full(deps)
+            crate::rpc::create_full::<_, _, FullBackend>(deps)
         })
     };
 
@@ -180,6 +290,52 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
         config,
     })?;
 
+    if sealing != Sealing::Aura {
+        let proposer = sc_basic_authorship::ProposerFactory::new(
+            client.clone(),
+            transaction_pool.clone(),
+            prometheus_registry.as_ref(),
+        );
+
+        let commands_stream = match sealing {
+            Sealing::Instant => {
+                // seal a new block on each imported transaction,
+                // there is no finality gadget so the block is finalized immediately.
+                let pool_stream = transaction_pool.import_notification_stream().map(|_| {
+                    EngineCommand::SealNewBlock {
+                        create_empty: false,
+                        finalize: true,
+                        parent_hash: None,
+                        sender: None,
+                    }
+                });
+                futures::stream::select(pool_stream, commands_stream).boxed()
+            }
+            _ => commands_stream.boxed(),
+        };
+
+        let authorship_future =
+            sc_consensus_manual_seal::run_manual_seal(sc_consensus_manual_seal::ManualSealParams {
+                block_import: client.clone(),
+                env: proposer,
+                client,
+                pool: transaction_pool.pool().clone(),
+                commands_stream,
+                select_chain,
+                consensus_data_provider: None,
+                inherent_data_providers,
+            });
+
+        // the authorship task is considered essential, i.e. if it
+        // fails we take down the service with it.
+        task_manager
+            .spawn_essential_handle()
+            .spawn_blocking("manual-seal", authorship_future);
+
+        network_starter.start_network();
+        return Ok(task_manager);
+    }
+
     if role.is_authority() {
         let proposer = sc_basic_authorship::ProposerFactory::new(
             client.clone(),
@@ -219,16 +375,19 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
     };
 
     let grandpa_config = sc_finality_grandpa::Config {
-        // FIXME #1578 make this available through chainspec
-        gossip_duration: Duration::from_millis(333),
-        justification_period: 512,
+        gossip_duration: Duration::from_millis(finality.gossip_duration),
+        justification_period: finality.justification_period,
         name: Some(name),
         observer_enabled: false,
         keystore,
         is_authority: role.is_network_authority(),
     };
 
-    if enable_grandpa {
+    // `sc-finality-grandpa` doesn't expose the observer protocol, so observing nodes
+    // don't vote and follow finality by the justifications imported with the blocks.
+    let observer = finality.observer_enabled && !role.is_authority();
+
+    if enable_grandpa && !observer {
         // start the full GRANDPA voter
         // NOTE: non-authorities could run the GRANDPA observer protocol, but at
         // this point the full voter should provide better guarantees of block
@@ -321,12 +480,21 @@ pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
         );
     }
 
+    let light_deps = crate::rpc::LightDeps {
+        remote_blockchain: backend.remote_blockchain(),
+        fetcher: on_demand.clone(),
+        client: client.clone(),
+        pool: transaction_pool.clone(),
+    };
+
+    let rpc_extensions = crate::rpc::create_light(light_deps);
+
     sc_service::spawn_tasks(sc_service::SpawnTasksParams {
         remote_blockchain: Some(backend.remote_blockchain()),
         transaction_pool,
         task_manager: &mut task_manager,
         on_demand: Some(on_demand),
-        rpc_extensions_builder: Box::new(|_, _| ()),
+        rpc_extensions_builder: Box::new(sc_service::NoopRpcExtensionBuilder(rpc_extensions)),
         telemetry_connection_sinks: sc_service::TelemetryConnectionSinks::default(),
         config,
         client,
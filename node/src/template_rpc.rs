@@ -0,0 +1,474 @@
+//! RPC methods specific to `pallet_template`.
+
+use crate::indexer::{block_records, EventIndex, HistoryRecord, RegistryEvent};
+use codec::{Decode, Encode};
+use frame_metadata::{DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed};
+use futures::{future, StreamExt, TryStreamExt};
+use jsonrpc_core::{
+    futures::{Future, Sink},
+    Error, ErrorCode, Result,
+};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use node_template_runtime::{
+    opaque::Block, pallet_template, AccountId, BlockNumber, Hash, Runtime,
+};
+use sc_client_api::{Backend, BlockchainEvents, StateBackend, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_api::{Metadata, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{blake2_128, storage::StorageKey, twox_128};
+use sp_runtime::{
+    generic::BlockId,
+    traits::{BlakeTwo256, Block as BlockT, Header as _},
+};
+use std::{marker::PhantomData, pin::Pin, sync::Arc};
+
+/// Maximal number of records returned by a single `templateModule_accountHistory` call.
+const MAX_HISTORY_LIMIT: u32 = 1000;
+
+/// The node is started without `--index-events`.
+const INDEX_DISABLED: i64 = 1;
+/// Runtime metadata is not available or can't be decoded.
+const METADATA_ERROR: i64 = 2;
+/// Runtime has no module or error with the given index.
+const UNKNOWN_ERROR: i64 = 3;
+/// Runtime state can't be read or decoded.
+pub(crate) const STATE_ERROR: i64 = 4;
+/// Events filter has unknown event kinds.
+const INVALID_FILTER: i64 = 5;
+
+/// Result of the RPC methods served asynchronously.
+pub type FutureResult<T> = Box<dyn Future<Item = T, Error = Error> + Send>;
+
+/// `pallet_template::AccountRegistry` entry of the account.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryAccount {
+    /// Raw value of the account roles, zero for accounts out of the registry.
+    pub roles: u8,
+    /// Creation time of the account, milliseconds.
+    pub create_time: u64,
+}
+
+/// Human-readable description of `DispatchError::Module`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorDescription {
+    /// Name of the module the error is declared in.
+    pub module: String,
+    /// Name of the error variant.
+    pub error: String,
+    /// Doc comment of the error variant.
+    pub documentation: String,
+}
+
+/// Filter of `templateModule_subscribeEvents` notifications.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EventFilter {
+    /// Only events related to the account.
+    pub account: Option<AccountId>,
+    /// Only events of the kinds, as in the `type` field of the event.
+    pub kinds: Option<Vec<String>>,
+    /// Notify about finalized blocks instead of the new best ones.
+    pub finalized: bool,
+}
+
+impl EventFilter {
+    /// Check that the filter has known event kinds only.
+    fn validate(&self) -> Result<()> {
+        match self
+            .kinds
+            .iter()
+            .flatten()
+            .find(|kind| !RegistryEvent::KINDS.contains(&kind.as_str()))
+        {
+            Some(kind) => Err(rpc_error(
+                INVALID_FILTER,
+                format!("Unknown event kind {}", kind),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn matches(&self, record: &HistoryRecord) -> bool {
+        let account_matches = self
+            .account
+            .as_ref()
+            .map_or(true, |account| record.event.accounts().contains(&account));
+        let kind_matches = self.kinds.as_ref().map_or(true, |kinds| {
+            kinds.iter().any(|kind| kind == record.event.kind())
+        });
+        account_matches && kind_matches
+    }
+}
+
+/// `pallet_template` RPC methods.
+// `jsonrpc-derive` generates a `return` at the end of the pub-sub methods.
+#[allow(clippy::needless_return)]
+#[rpc(server)]
+pub trait TemplateApi<BlockHash> {
+    /// RPC metadata
+    type Metadata;
+
+    /// Registry events related to the account starting from `from_block`, oldest first.
+    /// Served by nodes started with `--index-events` only.
+    #[rpc(name = "templateModule_accountHistory")]
+    fn account_history(
+        &self,
+        account: AccountId,
+        from_block: Option<BlockNumber>,
+        limit: Option<u32>,
+    ) -> Result<Vec<HistoryRecord>>;
+
+    /// Describe `DispatchError::Module { index, error }` using the runtime metadata
+    /// at the given block, the best block by default.
+    #[rpc(name = "templateModule_errorDescription")]
+    fn error_description(
+        &self,
+        index: u8,
+        error: u8,
+        at: Option<BlockHash>,
+    ) -> Result<ErrorDescription>;
+
+    /// Registry events of the imported best blocks, or the finalized ones, matching the filter.
+    /// Each notification holds the matching events of a single block.
+    /// Filters with unknown event kinds are rejected.
+    #[pubsub(
+        subscription = "templateModule_events",
+        subscribe,
+        name = "templateModule_subscribeEvents"
+    )]
+    fn subscribe_events(
+        &self,
+        metadata: Self::Metadata,
+        subscriber: Subscriber<Vec<HistoryRecord>>,
+        filter: Option<EventFilter>,
+    );
+
+    /// Cancel the events subscription.
+    #[pubsub(
+        subscription = "templateModule_events",
+        unsubscribe,
+        name = "templateModule_unsubscribeEvents"
+    )]
+    fn unsubscribe_events(
+        &self,
+        metadata: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool>;
+}
+
+/// `pallet_template` state queries, served by both full and light nodes.
+#[rpc(server)]
+pub trait TemplateStateApi<BlockHash> {
+    /// Registry entry of the account at the given block, the best block by default.
+    #[rpc(name = "templateModule_account")]
+    fn account(&self, account: AccountId, at: Option<BlockHash>) -> FutureResult<RegistryAccount>;
+}
+
+/// Implementation of `TemplateApi` backed by the client and the local events index.
+pub struct Template<C, B> {
+    client: Arc<C>,
+    index: Option<EventIndex>,
+    subscriptions: SubscriptionManager,
+    _backend: PhantomData<B>,
+}
+
+impl<C, B> Template<C, B> {
+    /// Create new `Template` with the given client, events index and subscriptions manager.
+    pub fn new(
+        client: Arc<C>,
+        index: Option<EventIndex>,
+        subscriptions: SubscriptionManager,
+    ) -> Self {
+        Template {
+            client,
+            index,
+            subscriptions,
+            _backend: PhantomData,
+        }
+    }
+}
+
+/// Implementation of `TemplateStateApi` reading the local state of the full node.
+pub struct TemplateState<C, B> {
+    client: Arc<C>,
+    _backend: PhantomData<B>,
+}
+
+impl<C, B> TemplateState<C, B> {
+    /// Create new `TemplateState` with the given client.
+    pub fn new(client: Arc<C>) -> Self {
+        TemplateState {
+            client,
+            _backend: PhantomData,
+        }
+    }
+}
+
+/// Storage key of the `pallet_template::AccountRegistry` entry of the account.
+pub fn account_storage_key(account: &AccountId) -> StorageKey {
+    let account = account.encode();
+    StorageKey(
+        [
+            &twox_128(b"TemplateModule")[..],
+            &twox_128(b"AccountRegistry")[..],
+            &blake2_128(&account)[..],
+            &account[..],
+        ]
+        .concat(),
+    )
+}
+
+/// Decode the `pallet_template::AccountRegistry` entry, accounts out of the registry have no roles.
+pub fn decode_account(data: Option<Vec<u8>>) -> Result<RegistryAccount> {
+    let account = match data {
+        Some(data) => pallet_template::AccountOf::<Runtime>::decode(&mut &data[..])
+            .map_err(|e| rpc_error(STATE_ERROR, e.to_string()))?,
+        None => Default::default(),
+    };
+    Ok(RegistryAccount {
+        roles: account.roles.bits(),
+        create_time: account.create_time,
+    })
+}
+
+pub(crate) fn rpc_error(code: i64, message: impl Into<String>) -> Error {
+    Error {
+        code: ErrorCode::ServerError(code),
+        message: message.into(),
+        data: None,
+    }
+}
+
+fn decoded<B, O: Clone>(value: &DecodeDifferent<B, O>) -> Option<O> {
+    match value {
+        DecodeDifferent::Decoded(value) => Some(value.clone()),
+        DecodeDifferent::Encode(_) => None,
+    }
+}
+
+/// Describe `DispatchError::Module { index, error }` using the encoded runtime metadata.
+fn describe_error(metadata: &[u8], index: u8, error: u8) -> Result<ErrorDescription> {
+    let metadata = RuntimeMetadataPrefixed::decode(&mut &metadata[..])
+        .map_err(|e| rpc_error(METADATA_ERROR, e.to_string()))?;
+    let modules = match metadata.1 {
+        RuntimeMetadata::V12(metadata) => decoded(&metadata.modules),
+        _ => None,
+    }
+    .ok_or_else(|| rpc_error(METADATA_ERROR, "Unsupported metadata version"))?;
+
+    let module = modules
+        .into_iter()
+        .find(|module| module.index == index)
+        .ok_or_else(|| rpc_error(UNKNOWN_ERROR, format!("Unknown module {}", index)))?;
+    decoded(&module.errors)
+        .and_then(|errors| errors.into_iter().nth(error as usize))
+        .and_then(|e| {
+            Some(ErrorDescription {
+                module: decoded(&module.name)?,
+                error: decoded(&e.name)?,
+                documentation: decoded(&e.documentation)?
+                    .iter()
+                    .map(|line| line.trim())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            })
+        })
+        .ok_or_else(|| {
+            rpc_error(
+                UNKNOWN_ERROR,
+                format!("Unknown error {} of module {}", error, index),
+            )
+        })
+}
+
+impl<C, B> TemplateApi<<Block as BlockT>::Hash> for Template<C, B>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C: BlockchainEvents<Block> + StorageProvider<Block, B>,
+    C::Api: Metadata<Block>,
+    B: Backend<Block> + Send + Sync + 'static,
+    B::State: StateBackend<BlakeTwo256>,
+{
+    type Metadata = sc_rpc::Metadata;
+
+    fn account_history(
+        &self,
+        account: AccountId,
+        from_block: Option<BlockNumber>,
+        limit: Option<u32>,
+    ) -> Result<Vec<HistoryRecord>> {
+        let index = self
+            .index
+            .as_ref()
+            .ok_or_else(|| rpc_error(INDEX_DISABLED, "Node is started without --index-events"))?;
+        let limit = limit.unwrap_or(MAX_HISTORY_LIMIT).min(MAX_HISTORY_LIMIT);
+        Ok(index.account_history(&account, from_block.unwrap_or_default(), limit as usize))
+    }
+
+    fn error_description(
+        &self,
+        index: u8,
+        error: u8,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<ErrorDescription> {
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let metadata = self
+            .client
+            .runtime_api()
+            .metadata(&at)
+            .map_err(|e| rpc_error(METADATA_ERROR, format!("{:?}", e)))?;
+        describe_error(&metadata, index, error)
+    }
+
+    fn subscribe_events(
+        &self,
+        _metadata: Self::Metadata,
+        subscriber: Subscriber<Vec<HistoryRecord>>,
+        filter: Option<EventFilter>,
+    ) {
+        let filter = filter.unwrap_or_default();
+        if let Err(e) = filter.validate() {
+            let _ = subscriber.reject(e);
+            return;
+        }
+        let blocks: Pin<Box<dyn futures::Stream<Item = (Hash, BlockNumber)> + Send>> =
+            if filter.finalized {
+                Box::pin(
+                    self.client
+                        .finality_notification_stream()
+                        .map(|n| (n.hash, *n.header.number())),
+                )
+            } else {
+                Box::pin(
+                    self.client
+                        .import_notification_stream()
+                        .filter(|n| future::ready(n.is_new_best))
+                        .map(|n| (n.hash, *n.header.number())),
+                )
+            };
+        let client = self.client.clone();
+        let stream = blocks
+            .filter_map(move |(hash, block_number)| {
+                let records = match block_records(&*client, hash, block_number) {
+                    Ok(records) => records,
+                    Err(e) => {
+                        log::warn!("Failed to read events of block #{}: {}", block_number, e);
+                        Vec::new()
+                    }
+                };
+                let records: Vec<_> = records
+                    .into_iter()
+                    .filter(|record| filter.matches(record))
+                    .collect();
+                future::ready(if records.is_empty() {
+                    None
+                } else {
+                    Some(records)
+                })
+            })
+            .map(|records| Ok::<_, ()>(Ok(records)))
+            .compat();
+
+        self.subscriptions.add(subscriber, |sink| {
+            sink.sink_map_err(|e| log::warn!("Error sending events notification: {:?}", e))
+                .send_all(stream)
+                // The stream ends when the subscriber is gone
+                .map(|_| ())
+        });
+    }
+
+    fn unsubscribe_events(
+        &self,
+        _metadata: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool> {
+        Ok(self.subscriptions.cancel(id))
+    }
+}
+
+impl<C, B> TemplateStateApi<<Block as BlockT>::Hash> for TemplateState<C, B>
+where
+    C: HeaderBackend<Block> + StorageProvider<Block, B> + Send + Sync + 'static,
+    B: Backend<Block> + Send + Sync + 'static,
+    B::State: StateBackend<BlakeTwo256>,
+{
+    fn account(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> FutureResult<RegistryAccount> {
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let account = self
+            .client
+            .storage(&at, &account_storage_key(&account))
+            .map_err(|e| rpc_error(STATE_ERROR, e.to_string()))
+            .and_then(|data| decode_account(data.map(|data| data.0)));
+        Box::new(jsonrpc_core::futures::future::result(account))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_runtime::DispatchError;
+
+    fn metadata() -> Vec<u8> {
+        Runtime::metadata().encode()
+    }
+
+    #[test]
+    fn it_describes_known_error() {
+        let (index, error) =
+            match DispatchError::from(pallet_template::Error::<Runtime>::CannotDisableSelf) {
+                DispatchError::Module { index, error, .. } => (index, error),
+                e => panic!("unexpected error {:?}", e),
+            };
+        assert_eq!(
+            describe_error(&metadata(), index, error).unwrap(),
+            ErrorDescription {
+                module: "TemplateModule".into(),
+                error: "CannotDisableSelf".into(),
+                documentation: "Admin can't disable their own account or drop their own ADMIN role"
+                    .into(),
+            }
+        );
+    }
+
+    #[test]
+    fn it_rejects_unknown_module_and_error() {
+        let e = describe_error(&metadata(), u8::max_value(), 0).unwrap_err();
+        assert_eq!(e.code, ErrorCode::ServerError(UNKNOWN_ERROR));
+        assert_eq!(e.message, "Unknown module 255");
+
+        let index = match DispatchError::from(pallet_template::Error::<Runtime>::NotAuthorized) {
+            DispatchError::Module { index, .. } => index,
+            e => panic!("unexpected error {:?}", e),
+        };
+        let e = describe_error(&metadata(), index, u8::max_value()).unwrap_err();
+        assert_eq!(e.code, ErrorCode::ServerError(UNKNOWN_ERROR));
+    }
+
+    #[test]
+    fn it_validates_event_kinds() {
+        let mut filter = EventFilter::default();
+        assert!(filter.validate().is_ok());
+
+        filter.kinds = Some(vec!["accountCreated".into(), "lockSet".into()]);
+        assert!(filter.validate().is_ok());
+
+        filter.kinds = Some(vec!["accountCreated".into(), "AccountCreated".into()]);
+        let e = filter.validate().unwrap_err();
+        assert_eq!(e.code, ErrorCode::ServerError(INVALID_FILTER));
+        assert_eq!(e.message, "Unknown event kind AccountCreated");
+    }
+
+    #[test]
+    fn it_rejects_invalid_metadata() {
+        let e = describe_error(&[0, 1, 2], 0, 0).unwrap_err();
+        assert_eq!(e.code, ErrorCode::ServerError(METADATA_ERROR));
+    }
+}
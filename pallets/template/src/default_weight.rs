@@ -3,21 +3,129 @@ use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
 impl crate::WeightInfo for () {
     fn update_something() -> Weight {
         (1000000 as Weight)
-            .saturating_add(DbWeight::get().reads(2 as Weight))
-            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
     }
 
     fn account_transfer_and_lock() -> Weight {
         (1000000 as Weight)
-            .saturating_add(DbWeight::get().reads(1 as Weight))
-            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
     }
 
     fn account_add() -> Weight {
-        (1_000_000 as Weight).saturating_add(DbWeight::get().writes(1))
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(8, 6))
     }
 
     fn account_disable() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(3, 4))
+    }
+
+    fn account_set_metadata() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(4, 3))
+    }
+
+    fn account_clear_metadata() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(3, 2))
+    }
+
+    fn request_role() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(3, 2))
+    }
+
+    fn approve_request() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(4, 4))
+    }
+
+    fn reject_request() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(3, 3))
+    }
+
+    fn force_account_add() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(1, 2))
+    }
+
+    fn force_account_disable() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(3, 4))
+    }
+
+    fn force_unlock(l: u32) -> Weight {
+        (1_000_000 as Weight)
+            .saturating_add((100_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(DbWeight::get().reads(1 + 2 * l as Weight))
+            .saturating_add(DbWeight::get().writes(2 * l as Weight))
+    }
+
+    fn set_lock() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(3, 2))
+    }
+
+    fn remove_lock() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(3, 2))
+    }
+
+    fn root_dispatch() -> Weight {
+        10_000_000 as Weight
+    }
+
+    fn account_add_batch(n: u32) -> Weight {
+        (1_000_000 as Weight)
+            .saturating_add((500_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(1 + 7 * n as Weight))
+            .saturating_add(DbWeight::get().writes(6 * n as Weight))
+    }
+
+    fn account_disable_batch(n: u32) -> Weight {
+        (1_000_000 as Weight)
+            .saturating_add((500_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(1 + 4 * n as Weight))
+            .saturating_add(DbWeight::get().writes(5 * n as Weight))
+    }
+
+    fn add_delegate() -> Weight {
         (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(1, 1))
     }
+
+    fn remove_delegate() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(1, 1))
+    }
+
+    fn delegated_account_add() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(10, 6))
+    }
+
+    fn delegated_account_disable() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(6, 5))
+    }
+
+    fn create_escrow() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(4, 3))
+    }
+
+    fn release_escrow() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(4, 3))
+    }
+
+    fn cancel_escrow() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(3, 2))
+    }
+
+    fn set_config() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().reads_writes(3, 5))
+    }
+
+    fn set_config_writers() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().writes(1))
+    }
+
+    fn set_rate_limit() -> Weight {
+        (1_000_000 as Weight).saturating_add(DbWeight::get().writes(1))
+    }
+
+    fn report_stale_accounts(n: u32) -> Weight {
+        (1_000_000 as Weight)
+            .saturating_add((100_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(2 * n as Weight + 1))
+            .saturating_add(DbWeight::get().writes(n as Weight + 1))
+    }
 }
@@ -0,0 +1,397 @@
+//! Property-based tests applying random sequences of registry and balance calls to the mock
+//! runtime and checking the registry invariants after each call.
+
+use crate::mock::*;
+use crate::{LockReasons, RawEvent, ALL_PERMISSIONS, FEE_LOCK_ID};
+use frame_support::{
+    dispatch::DispatchResult,
+    storage_root,
+    traits::{LockIdentifier, UnfilteredDispatchable},
+};
+use proptest::prelude::*;
+use sp_runtime::traits::Zero;
+
+type System = frame_system::Module<Test>;
+type Balances = pallet_balances::Module<Test>;
+
+/// Accounts the calls are made by and for, only the first one is funded on genesis.
+const ACCOUNTS: u64 = 5;
+
+/// Lock category of `set_lock` besides `FEE_LOCK_ID`.
+const OTHER_LOCK_ID: LockIdentifier = *b"othrlock";
+
+#[derive(Clone, Debug)]
+enum Action {
+    AccountAdd {
+        who: u64,
+        account: u64,
+        role: TestRoles,
+    },
+    AccountDisable {
+        who: u64,
+        whom: u64,
+    },
+    TransferAndLock {
+        who: u64,
+        whom: u64,
+        amount: Balance,
+    },
+    UpdateSomething {
+        who: u64,
+        value: u32,
+    },
+    Transfer {
+        from: u64,
+        to: u64,
+        amount: Balance,
+    },
+    AddDelegate {
+        who: u64,
+        delegate: u64,
+        permissions: u8,
+        expiry: u64,
+    },
+    DelegatedAccountAdd {
+        delegate: u64,
+        admin: u64,
+        account: u64,
+        role: TestRoles,
+    },
+    DelegatedAccountDisable {
+        delegate: u64,
+        admin: u64,
+        whom: u64,
+    },
+    SetLock {
+        who: u64,
+        account: u64,
+        id: LockIdentifier,
+        amount: Balance,
+        reasons: LockReasons,
+    },
+    RemoveLock {
+        who: u64,
+        account: u64,
+        id: LockIdentifier,
+    },
+    RequestRole {
+        who: u64,
+        role: TestRoles,
+    },
+    ApproveRequest {
+        who: u64,
+        account: u64,
+    },
+}
+
+impl Action {
+    fn apply(&self) -> DispatchResult {
+        let (who, call) = match *self {
+            Action::AccountAdd { who, account, role } => (
+                who,
+                Call::TemplateModule(crate::Call::account_add(account, role)),
+            ),
+            Action::AccountDisable { who, whom } => (
+                who,
+                Call::TemplateModule(crate::Call::account_disable(whom)),
+            ),
+            Action::TransferAndLock { who, whom, amount } => (
+                who,
+                Call::TemplateModule(crate::Call::account_transfer_and_lock(whom, amount)),
+            ),
+            Action::UpdateSomething { who, value } => (
+                who,
+                Call::TemplateModule(crate::Call::update_something(value)),
+            ),
+            Action::Transfer { from, to, amount } => (
+                from,
+                Call::Balances(pallet_balances::Call::transfer(to, amount)),
+            ),
+            Action::AddDelegate {
+                who,
+                delegate,
+                permissions,
+                expiry,
+            } => (
+                who,
+                Call::TemplateModule(crate::Call::add_delegate(delegate, permissions, expiry)),
+            ),
+            Action::DelegatedAccountAdd {
+                delegate,
+                admin,
+                account,
+                role,
+            } => (
+                delegate,
+                Call::TemplateModule(crate::Call::delegated_account_add(admin, account, role)),
+            ),
+            Action::DelegatedAccountDisable {
+                delegate,
+                admin,
+                whom,
+            } => (
+                delegate,
+                Call::TemplateModule(crate::Call::delegated_account_disable(admin, whom)),
+            ),
+            Action::SetLock {
+                who,
+                account,
+                id,
+                amount,
+                reasons,
+            } => (
+                who,
+                Call::TemplateModule(crate::Call::set_lock(account, id, amount, reasons)),
+            ),
+            Action::RemoveLock { who, account, id } => (
+                who,
+                Call::TemplateModule(crate::Call::remove_lock(account, id)),
+            ),
+            Action::RequestRole { who, role } => (
+                who,
+                Call::TemplateModule(crate::Call::request_role(role, vec![])),
+            ),
+            Action::ApproveRequest { who, account } => (
+                who,
+                Call::TemplateModule(crate::Call::approve_request(account)),
+            ),
+        };
+        call.dispatch_bypass_filter(Origin::signed(who))
+            .map(|_| ())
+            .map_err(|e| e.error)
+    }
+}
+
+fn account() -> impl Strategy<Value = u64> {
+    1..=ACCOUNTS
+}
+
+fn role() -> impl Strategy<Value = TestRoles> {
+    prop_oneof![
+        Just(TestRoles::empty()),
+        Just(TestRoles::ADMIN),
+        Just(TestRoles::USER),
+        Just(TestRoles::AUDITOR),
+        Just(TestRoles::ADMIN | TestRoles::USER),
+    ]
+}
+
+// Zero and amounts around the existential deposit and the initial balance
+fn amount() -> impl Strategy<Value = Balance> {
+    prop_oneof![Just(0), 0..200 as Balance, 0..=2 * INITIAL_BALANCE]
+}
+
+fn lock_id() -> impl Strategy<Value = LockIdentifier> {
+    prop_oneof![Just(FEE_LOCK_ID), Just(OTHER_LOCK_ID)]
+}
+
+fn lock_reasons() -> impl Strategy<Value = LockReasons> {
+    prop_oneof![Just(LockReasons::ExceptFee), Just(LockReasons::All)]
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        (account(), account(), role()).prop_map(|(who, account, role)| Action::AccountAdd {
+            who,
+            account,
+            role
+        }),
+        (account(), account()).prop_map(|(who, whom)| Action::AccountDisable { who, whom }),
+        (account(), account(), amount()).prop_map(|(who, whom, amount)| Action::TransferAndLock {
+            who,
+            whom,
+            amount
+        }),
+        (account(), any::<u32>()).prop_map(|(who, value)| Action::UpdateSomething { who, value }),
+        (account(), account(), amount()).prop_map(|(from, to, amount)| Action::Transfer {
+            from,
+            to,
+            amount
+        }),
+        // Expiry 1 is the current block, such delegation is refused
+        (account(), account(), 0..=ALL_PERMISSIONS + 1, 1..4u64).prop_map(
+            |(who, delegate, permissions, expiry)| Action::AddDelegate {
+                who,
+                delegate,
+                permissions,
+                expiry
+            }
+        ),
+        (account(), account(), account(), role()).prop_map(|(delegate, admin, account, role)| {
+            Action::DelegatedAccountAdd {
+                delegate,
+                admin,
+                account,
+                role,
+            }
+        }),
+        (account(), account(), account()).prop_map(|(delegate, admin, whom)| {
+            Action::DelegatedAccountDisable {
+                delegate,
+                admin,
+                whom,
+            }
+        }),
+        (account(), account(), lock_id(), amount(), lock_reasons()).prop_map(
+            |(who, account, id, amount, reasons)| Action::SetLock {
+                who,
+                account,
+                id,
+                amount,
+                reasons
+            }
+        ),
+        (account(), account(), lock_id()).prop_map(|(who, account, id)| Action::RemoveLock {
+            who,
+            account,
+            id
+        }),
+        (account(), role()).prop_map(|(who, role)| Action::RequestRole { who, role }),
+        (account(), account()).prop_map(|(who, account)| Action::ApproveRequest { who, account }),
+    ]
+}
+
+fn roles() -> Vec<TestRoles> {
+    (1..=ACCOUNTS)
+        .map(|acc| TemplateModule::account_registry(acc).roles)
+        .collect()
+}
+
+fn template_events() -> Vec<crate::Event<Test>> {
+    let events = System::events()
+        .into_iter()
+        .filter_map(|evt| match evt.event {
+            TestEvent::template(event) => Some(event),
+            _ => None,
+        })
+        .collect();
+    System::reset_events();
+    events
+}
+
+// Zero locks are not kept by the balances pallet
+fn has_lock(account: u64, id: LockIdentifier, amount: Balance) -> bool {
+    match Balances::locks(account).iter().find(|lock| lock.id == id) {
+        Some(lock) => lock.amount == amount,
+        None => amount.is_zero(),
+    }
+}
+
+fn check_invariants() {
+    // At least one admin, `Admins` follows the registry
+    let admins: Vec<u64> = (1..=ACCOUNTS)
+        .filter(|acc| TemplateModule::account_is_admin(acc))
+        .collect();
+    assert!(!admins.is_empty(), "registry has no admins");
+    assert_eq!(TemplateModule::admins(), admins);
+
+    for acc in 1..=ACCOUNTS {
+        // Locks never exceed the free balance. Transaction fees can be paid from `ExceptFee`
+        // locks, the mock runtime doesn't charge them.
+        let free = Balances::free_balance(acc);
+        for lock in Balances::locks(acc) {
+            assert!(
+                lock.amount <= free,
+                "lock {:?} of {} exceeds free balance {}",
+                lock,
+                acc,
+                free
+            );
+        }
+        // Registry locks are set in the balances pallet
+        for (id, info) in TemplateModule::account_locks(&acc) {
+            assert!(
+                has_lock(acc, id, info.amount),
+                "lock {:?} of {} is not set",
+                id,
+                acc
+            );
+        }
+    }
+}
+
+// Events of the successful call describe its storage changes
+fn check_events(roles_before: &[TestRoles], something_before: Option<u32>) {
+    let events = template_events();
+    for event in &events {
+        match event {
+            RawEvent::AccountCreated(_, account, role) => {
+                assert_eq!(TemplateModule::account_registry(account).roles, *role)
+            }
+            RawEvent::AccountDisabled(_, account) | RawEvent::AccountReaped(account, false) => {
+                assert!(!TemplateModule::account_registry(account).is_enable())
+            }
+            RawEvent::BalanceLocked(account, amount) => {
+                assert!(has_lock(*account, FEE_LOCK_ID, *amount))
+            }
+            RawEvent::LockSet(_, account, id, amount) => assert!(has_lock(*account, *id, *amount)),
+            RawEvent::LockRemoved(_, account, id) => {
+                assert!(!Balances::locks(account).iter().any(|lock| lock.id == *id))
+            }
+            RawEvent::RoleRequested(who, role, _) => assert_eq!(
+                TemplateModule::pending_requests(who).map(|request| request.role),
+                Some(*role)
+            ),
+            RawEvent::RoleRequestApproved(_, account, _) => {
+                assert!(TemplateModule::pending_requests(account).is_none())
+            }
+            RawEvent::DelegateAdded(admin, delegate, permissions, expiry) => {
+                let delegation = TemplateModule::delegations(admin, delegate).unwrap();
+                assert_eq!(
+                    (delegation.permissions, delegation.expiry),
+                    (*permissions, *expiry)
+                );
+            }
+            RawEvent::SomethingStored(value, _) => {
+                assert_eq!(TemplateModule::something(), Some(*value))
+            }
+            _ => {}
+        }
+    }
+
+    for (acc, before) in (1..=ACCOUNTS).zip(roles_before) {
+        let after = TemplateModule::account_registry(acc).roles;
+        if after != *before {
+            assert!(
+                events.iter().any(|event| match event {
+                    RawEvent::AccountCreated(_, account, _)
+                    | RawEvent::AccountDisabled(_, account)
+                    | RawEvent::AccountReaped(account, false) => *account == acc,
+                    _ => false,
+                }),
+                "roles of {} changed without an event",
+                acc
+            );
+        }
+    }
+
+    if TemplateModule::something() != something_before {
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, RawEvent::SomethingStored(..))));
+    }
+}
+
+// 256 cases by default, `PROPTEST_CASES` environment variable overrides the number
+proptest! {
+    #[test]
+    fn it_keep_invariants(actions in prop::collection::vec(action(), 1..64)) {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            check_invariants();
+
+            for action in &actions {
+                let roles_before = roles();
+                let something_before = TemplateModule::something();
+                let root_before = storage_root();
+
+                match action.apply() {
+                    Ok(()) => check_events(&roles_before, something_before),
+                    // Failed calls change nothing
+                    Err(_) => assert_eq!(storage_root(), root_before, "{:?} changed storage", action),
+                }
+                check_invariants();
+            }
+        });
+    }
+}
@@ -1,31 +1,63 @@
 #![cfg_attr(not(feature = "std"), no_std)]
+#![recursion_limit = "256"]
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 
 use frame_support::{
     codec::{Decode, Encode},
-    debug, decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+    debug, decl_error, decl_event, decl_module, decl_storage,
+    dispatch::{self, DispatchResult},
+    ensure,
     sp_runtime::{
-        sp_std::ops::BitAnd,
-        traits::{AtLeast32Bit, Zero},
+        sp_std::{cmp::Ordering, fmt, marker::PhantomData, prelude::*},
+        traits::{
+            AtLeast32Bit, AtLeast32BitUnsigned, DispatchInfoOf, Dispatchable, IdentifyAccount,
+            Saturating, SignedExtension, Zero,
+        },
+        transaction_validity::{
+            InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
+            TransactionValidityError, ValidTransaction,
+        },
+        KeyTypeId, RuntimeDebug,
     },
+    storage::migration,
     traits::{
-        Currency, ExistenceRequirement::AllowDeath, Get, LockIdentifier, LockableCurrency,
-        OnKilledAccount, WithdrawReason, WithdrawReasons,
+        ChangeMembers, Currency, EnsureOrigin, ExistenceRequirement::AllowDeath, Get, Imbalance,
+        LockIdentifier, LockableCurrency, OnKilledAccount, ReservableCurrency,
+        UnfilteredDispatchable, WithdrawReason, WithdrawReasons,
     },
-    weights::{DispatchClass, Pays, Weight},
+    transactional,
+    weights::{DispatchClass, DispatchInfo, GetDispatchInfo, Pays, Weight},
     Parameter,
 };
-use frame_system::ensure_signed;
+use frame_system::{
+    ensure_none, ensure_signed,
+    offchain::{
+        AppCrypto, SendTransactionTypes, SendUnsignedTransaction, SignedPayload, Signer,
+        SigningTypes,
+    },
+    RawOrigin,
+};
 
 mod default_weight;
 #[cfg(test)]
+mod fuzz;
+#[cfg(test)]
 mod mock;
 #[cfg(feature = "payment")]
 mod payment;
+mod roles;
 #[cfg(test)]
 mod tests;
 
+pub use roles::RoleSet;
+// Used by `role_set!`
+#[doc(hidden)]
+pub use frame_support::codec;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use serde;
+
 pub mod prelude {
     #[cfg(feature = "payment")]
     pub use crate::payment::IdentityMultiplierUpdater;
@@ -39,53 +71,315 @@ pub struct Account<Moment, AccountRole> {
     pub create_time: Moment,
 }
 
-impl<
-        Moment: Default + AtLeast32Bit + Copy,
-        AccountRole: Zero + Copy + From<u8> + BitAnd<Output = AccountRole>,
-    > Account<Moment, AccountRole>
-{
+impl<Moment: Default + AtLeast32Bit + Copy, AccountRole: RoleSet> Account<Moment, AccountRole> {
     pub fn is_admin(&self) -> bool {
-        !(self.roles & ADMIN_ROLE.into()).is_zero()
+        self.roles.contains(AccountRole::ADMIN)
     }
     pub fn is_enable(&self) -> bool {
-        !self.roles.is_zero()
+        !self.roles.is_empty()
     }
-    pub fn is_role_correct(_role: AccountRole) -> bool {
-        true
+    /// Role contains roles known to the runtime only.
+    pub fn is_role_correct(role: AccountRole) -> bool {
+        role.is_known()
     }
 
-    #[allow(dead_code)]
     pub fn age(&self, now: Moment) -> Moment {
         now - self.create_time
     }
 
     pub fn new_admin() -> Self {
         Account {
-            roles: ADMIN_ROLE.into(),
+            roles: AccountRole::ADMIN,
             create_time: Default::default(),
         }
     }
 }
 
 pub type AccountOf<T> = Account<<T as pallet_timestamp::Trait>::Moment, <T as Trait>::AccountRole>;
-const FEE_LOCK_ID: LockIdentifier = *b"fee lock";
+
+/// Delegate permissions. Combine values to grant several permissions.
+pub const PERMISSION_ACCOUNT_ADD: u8 = 0x01;
+pub const PERMISSION_ACCOUNT_DISABLE: u8 = 0x02;
+const ALL_PERMISSIONS: u8 = PERMISSION_ACCOUNT_ADD | PERMISSION_ACCOUNT_DISABLE;
+
+/// Permissions granted by an admin to a delegate account until `expiry` block.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Delegation<BlockNumber> {
+    pub permissions: u8,
+    pub expiry: BlockNumber,
+}
+
+/// What happens to the registry entry when the account is reaped by the balances pallet.
+/// The entry of the last admin is always kept.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ReapPolicy {
+    /// Remove the entry.
+    Remove,
+    /// Keep the entry.
+    Keep,
+    /// Keep the entries of admins only.
+    KeepAdmins,
+}
+
+impl Default for ReapPolicy {
+    fn default() -> Self {
+        ReapPolicy::Remove
+    }
+}
+
+/// Metadata attached to an account of the registry.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct AccountMetadata<Hash> {
+    /// Display name.
+    pub name: Vec<u8>,
+    /// Reference of the account in external systems.
+    pub external_id: Vec<u8>,
+    /// Hash of the off-chain JSON document.
+    pub json_hash: Option<Hash>,
+}
+
+impl<Hash> AccountMetadata<Hash> {
+    /// Number of bytes the deposit is taken for.
+    pub fn deposit_len(&self) -> usize {
+        self.name.len() + self.external_id.len()
+    }
+}
+
+/// Metadata with the deposit reserved from the admin who has set it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct MetadataRecord<Hash, AccountId, Balance> {
+    pub metadata: AccountMetadata<Hash>,
+    pub depositor: AccountId,
+    pub deposit: Balance,
+}
+
+/// Role requested by an account with the reserved deposit.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct RoleRequest<AccountRole, Balance, Moment> {
+    pub role: AccountRole,
+    pub note: Vec<u8>,
+    pub deposit: Balance,
+    pub create_time: Moment,
+}
+
+pub type RoleRequestOf<T> =
+    RoleRequest<<T as Trait>::AccountRole, BalanceOf<T>, <T as pallet_timestamp::Trait>::Moment>;
+
+pub type MetadataRecordOf<T> = MetadataRecord<
+    <T as frame_system::Trait>::Hash,
+    <T as frame_system::Trait>::AccountId,
+    BalanceOf<T>,
+>;
+/// Lock category of `account_transfer_and_lock`.
+pub const FEE_LOCK_ID: LockIdentifier = *b"fee lock";
+
+/// Withdrawals forbidden by a registry lock.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum LockReasons {
+    /// Locked balance can be used to pay transaction fees only.
+    ExceptFee,
+    /// Locked balance can't be withdrawn at all.
+    All,
+}
+
+impl From<LockReasons> for WithdrawReasons {
+    fn from(reasons: LockReasons) -> Self {
+        match reasons {
+            LockReasons::ExceptFee => WithdrawReasons::except(WithdrawReason::TransactionPayment),
+            LockReasons::All => WithdrawReasons::all(),
+        }
+    }
+}
+
+/// Balance lock set by an admin.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct LockInfo<AccountId, Balance> {
+    pub amount: Balance,
+    pub reasons: LockReasons,
+    pub creator: AccountId,
+}
+
+pub type LockInfoOf<T> = LockInfo<<T as frame_system::Trait>::AccountId, BalanceOf<T>>;
+
+/// Identifier of an escrow.
+pub type EscrowId = u64;
+
+/// When the escrowed funds can be released to the counterparty.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ReleaseCondition<BlockNumber> {
+    /// Anyone can release the funds since the block.
+    After(BlockNumber),
+    /// A registry admin releases the funds.
+    AdminApproval,
+    /// The funds are released when both the creator and the counterparty approve.
+    BothParties,
+}
+
+/// Funds reserved from `creator` until they are released to `counterparty` or cancelled.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Escrow<AccountId, Balance, BlockNumber> {
+    pub creator: AccountId,
+    pub counterparty: AccountId,
+    pub amount: Balance,
+    pub condition: ReleaseCondition<BlockNumber>,
+    /// Parties approved the release, used by `ReleaseCondition::BothParties`.
+    pub approvals: Vec<AccountId>,
+}
+
+pub type EscrowOf<T> = Escrow<
+    <T as frame_system::Trait>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Trait>::BlockNumber,
+>;
+
+/// Key of the on-chain configuration store.
+pub type ConfigKey = Vec<u8>;
+
+/// Configuration key of the `Something` value, SCALE-encoded `u32`.
+pub const SOMETHING_KEY: &[u8] = b"something";
+
+/// Change of a configuration value. `None` value means the key has been removed.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ConfigChange<AccountId, BlockNumber> {
+    pub value: Option<Vec<u8>>,
+    pub who: AccountId,
+    pub block_number: BlockNumber,
+}
+
+pub type ConfigChangeOf<T> =
+    ConfigChange<<T as frame_system::Trait>::AccountId, <T as frame_system::Trait>::BlockNumber>;
+
+/// `CheckRateLimit` rejects the transaction because the sender has exhausted its free calls.
+pub const RATE_LIMIT_EXCEEDED: u8 = 1;
+
+/// Number of free calls allowed per `period` blocks.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct RateLimit<BlockNumber> {
+    pub calls: u32,
+    pub period: BlockNumber,
+}
+
+impl<BlockNumber: AtLeast32BitUnsigned + Copy> RateLimit<BlockNumber> {
+    /// Compare the numbers of calls allowed per block.
+    pub fn cmp_rate(&self, other: &Self) -> Ordering {
+        let rate = BlockNumber::from(self.calls).saturating_mul(other.period);
+        let other_rate = BlockNumber::from(other.calls).saturating_mul(self.period);
+        rate.cmp(&other_rate)
+    }
+}
+
+pub type RateLimitOf<T> = RateLimit<<T as frame_system::Trait>::BlockNumber>;
+
+/// `Get` implementation reading the SCALE-encoded configuration value of the key `K`.
+/// Returns `D` if the key is not set or the value can't be decoded, so runtime
+/// parameters can be managed by the configuration store.
+pub struct ConfigParameter<K, V, D>(PhantomData<(K, V, D)>);
+
+impl<K: Get<&'static [u8]>, V: Decode, D: Get<V>> Get<V> for ConfigParameter<K, V, D> {
+    fn get() -> V {
+        ConfigValues::get(K::get())
+            .and_then(|value| V::decode(&mut &value[..]).ok())
+            .unwrap_or_else(D::get)
+    }
+}
+
+/// Key type of the offchain worker keys.
+/// The key of an admin account has to be inserted into the node keystore
+/// to let the offchain worker report stale accounts.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"tmpl");
+/// Maximum number of accounts in a single stale accounts report.
+const MAX_STALE_ACCOUNTS_REPORT: usize = 64;
+
+/// Offchain worker crypto based on sr25519 keys.
+pub mod crypto {
+    use super::KEY_TYPE;
+    use frame_support::sp_runtime::{
+        app_crypto::{app_crypto, sr25519},
+        MultiSignature, MultiSigner,
+    };
+    app_crypto!(sr25519, KEY_TYPE);
+
+    /// Offchain worker signer for runtimes using `MultiSignature`.
+    pub struct TemplateAuthId;
+
+    impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for TemplateAuthId {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = sr25519::Signature;
+        type GenericPublic = sr25519::Public;
+    }
+}
+
+/// Stale accounts report built by the offchain worker and signed by an admin key.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct StaleAccountsPayload<Public, BlockNumber, AccountId> {
+    pub block_number: BlockNumber,
+    pub accounts: Vec<AccountId>,
+    pub public: Public,
+}
+
+impl<T: SigningTypes> SignedPayload<T>
+    for StaleAccountsPayload<T::Public, T::BlockNumber, T::AccountId>
+{
+    fn public(&self) -> T::Public {
+        self.public.clone()
+    }
+}
+
+pub type StaleAccountsPayloadOf<T> = StaleAccountsPayload<
+    <T as SigningTypes>::Public,
+    <T as frame_system::Trait>::BlockNumber,
+    <T as frame_system::Trait>::AccountId,
+>;
 
 /// Configure the pallet by specifying the parameters and types on which it depends.
-pub trait Trait: frame_system::Trait + pallet_timestamp::Trait {
+pub trait Trait:
+    frame_system::Trait + pallet_timestamp::Trait + SigningTypes + SendTransactionTypes<Call<Self>>
+{
     /// Because this pallet emits events, it depends on the runtime's definition of an event.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
     // Describe pallet constants.
     // Lean more https://substrate.dev/docs/en/knowledgebase/runtime/metadata
-    type AdminRole: Get<Self::AccountRole>;
-    /// Change  to u16 if it requires more than 8 distinct values
-    type AccountRole: Default
-        + Parameter
-        + PartialOrd
-        + Zero
-        + From<u8>
-        + Copy
-        + BitAnd<Output = Self::AccountRole>;
-    type Currency: LockableCurrency<Self::AccountId, Moment = Self::BlockNumber>;
+    /// Set of account roles, declared by the runtime with `role_set!`.
+    type AccountRole: RoleSet;
+    type Currency: LockableCurrency<Self::AccountId, Moment = Self::BlockNumber>
+        + ReservableCurrency<Self::AccountId>;
+    /// Maximal length of `name` and `external_id` metadata fields.
+    type MaxMetadataLen: Get<u32>;
+    /// Deposit reserved for account metadata.
+    type MetadataDepositBase: Get<BalanceOf<Self>>;
+    /// Deposit reserved per byte of account metadata.
+    type MetadataDepositPerByte: Get<BalanceOf<Self>>;
+    /// Deposit reserved on role request. Refunded on approval and slashed on rejection.
+    type RoleRequestDeposit: Get<BalanceOf<Self>>;
+    /// Deposit reserved from the sponsoring admin for a registry entry with ADMIN role.
+    type AdminDeposit: Get<BalanceOf<Self>>;
+    /// Deposit reserved from the sponsoring admin for a registry entry without ADMIN role.
+    type UserDeposit: Get<BalanceOf<Self>>;
+    /// Maximal number of locks per account, the same as `MaxLocks` of the balances pallet.
+    type MaxLocks: Get<u32>;
+    /// Maximal length of configuration keys and values.
+    type MaxConfigLen: Get<u32>;
+    /// Number of the last changes kept in the history of a configuration key.
+    type MaxConfigHistory: Get<u32>;
+    /// Origin allowed to manage the registry regardless of the admin accounts, e.g. `Root`.
+    type AdminOrigin: EnsureOrigin<Self::Origin>;
+    /// Handler of the admins set changes, e.g. `pallet_collective` council of the admins.
+    type AdminsChanged: ChangeMembers<Self::AccountId>;
+    /// Maximal number of accounts in a batch call.
+    type MaxBatchSize: Get<u32>;
+    /// Registry entries policy for the reaped accounts.
+    type ReapPolicy: Get<ReapPolicy>;
+    /// Call dispatched by `AdminOrigin` with `Root` origin.
+    type Call: Parameter + UnfilteredDispatchable<Origin = Self::Origin> + GetDispatchInfo;
+    /// Key used by the offchain worker to sign stale accounts reports.
+    type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+    /// Enabled non-admin accounts older than this age are reported as stale.
+    type StaleAccountAge: Get<Self::Moment>;
+    /// Minimal number of blocks between stale accounts reports.
+    type ReportInterval: Get<Self::BlockNumber>;
+    /// Priority of the unsigned stale accounts reports.
+    type UnsignedPriority: Get<TransactionPriority>;
     type WeightInfo: WeightInfo;
 }
 
@@ -94,18 +388,35 @@ pub trait WeightInfo {
     fn account_transfer_and_lock() -> Weight;
     fn account_disable() -> Weight;
     fn account_add() -> Weight;
+    fn report_stale_accounts(n: u32) -> Weight;
+    fn account_set_metadata() -> Weight;
+    fn account_clear_metadata() -> Weight;
+    fn request_role() -> Weight;
+    fn approve_request() -> Weight;
+    fn reject_request() -> Weight;
+    fn force_account_add() -> Weight;
+    fn force_account_disable() -> Weight;
+    fn force_unlock(l: u32) -> Weight;
+    fn set_lock() -> Weight;
+    fn remove_lock() -> Weight;
+    fn root_dispatch() -> Weight;
+    fn account_add_batch(n: u32) -> Weight;
+    fn account_disable_batch(n: u32) -> Weight;
+    fn add_delegate() -> Weight;
+    fn remove_delegate() -> Weight;
+    fn delegated_account_add() -> Weight;
+    fn delegated_account_disable() -> Weight;
+    fn create_escrow() -> Weight;
+    fn release_escrow() -> Weight;
+    fn cancel_escrow() -> Weight;
+    fn set_config() -> Weight;
+    fn set_config_writers() -> Weight;
+    fn set_rate_limit() -> Weight;
 }
 
 type BalanceOf<T> =
     <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
 
-/// Account roles . Add additional values if required
-#[allow(dead_code)]
-const NONE_ROLE: u8 = 0x00;
-pub const ADMIN_ROLE: u8 = 0x01;
-#[allow(dead_code)]
-const USER_ROLE: u8 = 0x02;
-
 // Storage, Events, Errors are declared using rust macros
 // How to use macros see
 // https://substrate.dev/docs/en/knowledgebase/runtime/macros
@@ -119,13 +430,83 @@ decl_storage! {
     trait Store for Module<T: Trait> as TemplateModule {
         // Learn more about declaring storage items:
         // https://substrate.dev/docs/en/knowledgebase/runtime/storage#declaring-storage-items
-        Something get(fn something): Option<u32>;
-
         AccountRegistry
             get(fn account_registry)
             config(genesis_account_registry):
             map hasher(blake2_128_concat) T::AccountId => AccountOf<T>;
 
+        /// Accounts reported as stale by the offchain worker with the report time.
+        StaleAccounts get(fn stale_accounts):
+            map hasher(blake2_128_concat) T::AccountId => Option<T::Moment>;
+
+        /// Stale accounts reports are not accepted before this block.
+        NextReportAt get(fn next_report_at): T::BlockNumber;
+
+        /// Metadata of the registry accounts.
+        Metadata get(fn metadata):
+            map hasher(blake2_128_concat) T::AccountId => Option<MetadataRecordOf<T>>;
+
+        /// Role requests waiting for an admin decision.
+        PendingRequests get(fn pending_requests):
+            map hasher(blake2_128_concat) T::AccountId => Option<RoleRequestOf<T>>;
+
+        /// Sorted list of the accounts with ADMIN role.
+        Admins get(fn admins): Vec<T::AccountId>;
+
+        /// Delegations of the admins to their hot keys. [admin, delegate]
+        Delegations get(fn delegations):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AccountId
+            => Option<Delegation<T::BlockNumber>>;
+
+        /// Deposits reserved for the registry entries. [sponsor, amount]
+        Deposits get(fn deposits):
+            map hasher(blake2_128_concat) T::AccountId => Option<(T::AccountId, BalanceOf<T>)>;
+
+        /// Balance locks set by the admins by category. [account, lock id]
+        AccountLocks get(fn account_lock):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) LockIdentifier
+            => Option<LockInfoOf<T>>;
+
+        /// Identifier of the next escrow.
+        NextEscrowId get(fn next_escrow_id): EscrowId;
+
+        /// Open escrows.
+        Escrows get(fn escrows): map hasher(twox_64_concat) EscrowId => Option<EscrowOf<T>>;
+
+        /// On-chain configuration values.
+        ConfigValues get(fn config_value): map hasher(blake2_128_concat) ConfigKey => Option<Vec<u8>>;
+
+        /// Roles allowed to change the configuration key. Admins only if not set.
+        ConfigWriters get(fn config_writers):
+            map hasher(blake2_128_concat) ConfigKey => Option<T::AccountRole>;
+
+        /// Number of changes of the configuration key.
+        ConfigRevision get(fn config_revision): map hasher(blake2_128_concat) ConfigKey => u32;
+
+        /// Last `MaxConfigHistory` changes of the configuration key. [key, revision]
+        ConfigHistory get(fn config_history):
+            double_map hasher(blake2_128_concat) ConfigKey, hasher(twox_64_concat) u32
+            => Option<ConfigChangeOf<T>>;
+
+        /// Limits of the free calls by roles. Limit of the empty roles applies to any account.
+        RateLimits get(fn rate_limits) config():
+            map hasher(twox_64_concat) T::AccountRole => Option<RateLimitOf<T>>;
+
+        /// Start block of the current rate limit period and the number of free calls made in it.
+        RateLimitUsage get(fn rate_limit_usage):
+            map hasher(blake2_128_concat) T::AccountId => (T::BlockNumber, u32);
+    }
+    add_extra_genesis {
+        build(|config: &GenesisConfig<T>| {
+            let mut admins: Vec<_> = config
+                .genesis_account_registry
+                .iter()
+                .filter(|(_, acc)| acc.is_admin())
+                .map(|(who, _)| who.clone())
+                .collect();
+            admins.sort();
+            Admins::<T>::put(admins);
+        });
     }
 }
 
@@ -137,6 +518,7 @@ decl_event!(
         AccountId = <T as frame_system::Trait>::AccountId,
         Balance = BalanceOf<T>,
         AccountRole = <T as Trait>::AccountRole,
+        BlockNumber = <T as frame_system::Trait>::BlockNumber,
     {
         // Event documentation should end with an array that provides descriptive names for event parameters.
         /// Something store value has been updated. [value, who]
@@ -147,6 +529,56 @@ decl_event!(
         AccountDisabled(AccountId, AccountId),
         /// Lock balance [who, balance]
         BalanceLocked(AccountId, Balance),
+        /// Account has been flagged as stale by the offchain worker [account]
+        AccountFlagged(AccountId),
+        /// Account metadata has been set [who, account, deposit]
+        MetadataSet(AccountId, AccountId, Balance),
+        /// Account metadata has been removed and the deposit refunded [account, depositor, deposit]
+        MetadataCleared(AccountId, AccountId, Balance),
+        /// Account has requested a role [who, role, deposit]
+        RoleRequested(AccountId, AccountRole, Balance),
+        /// Role request has been approved and the deposit refunded [who, account, role]
+        RoleRequestApproved(AccountId, AccountId, AccountRole),
+        /// Role request has been rejected and the deposit slashed [who, account, deposit]
+        RoleRequestRejected(AccountId, AccountId, Balance),
+        /// Account has been created by `AdminOrigin` [account, role]
+        AccountForceCreated(AccountId, AccountRole),
+        /// Account has been disabled by `AdminOrigin` [account]
+        AccountForceDisabled(AccountId),
+        /// All the registry locks have been removed by `AdminOrigin` [account]
+        BalanceUnlocked(AccountId),
+        /// Call has been dispatched with `Root` origin by `AdminOrigin` [result]
+        RootDispatched(DispatchResult),
+        /// Account has been reaped by the balances pallet [account, registry entry kept]
+        AccountReaped(AccountId, bool),
+        /// Admin has delegated permissions [admin, delegate, permissions, expiry]
+        DelegateAdded(AccountId, AccountId, u8, BlockNumber),
+        /// Delegation has been removed [admin, delegate]
+        DelegateRemoved(AccountId, AccountId),
+        /// Delegate has acted on behalf of the admin [admin, delegate]
+        DelegateActed(AccountId, AccountId),
+        /// Registry entry deposit has been reserved [sponsor, account, amount]
+        DepositReserved(AccountId, AccountId, Balance),
+        /// Registry entry deposit has been refunded [sponsor, account, amount]
+        DepositUnreserved(AccountId, AccountId, Balance),
+        /// Balance lock has been set [who, account, lock id, amount]
+        LockSet(AccountId, AccountId, LockIdentifier, Balance),
+        /// Balance lock has been removed [who, account, lock id]
+        LockRemoved(AccountId, AccountId, LockIdentifier),
+        /// Escrow has been created [escrow id, creator, counterparty, amount]
+        EscrowCreated(EscrowId, AccountId, AccountId, Balance),
+        /// Party has approved the escrow release [escrow id, who]
+        EscrowApproved(EscrowId, AccountId),
+        /// Escrowed funds have been transferred to the counterparty [escrow id, counterparty, amount]
+        EscrowReleased(EscrowId, AccountId, Balance),
+        /// Escrow has been cancelled and the funds refunded to the creator [escrow id, who]
+        EscrowCancelled(EscrowId, AccountId),
+        /// Configuration value has been changed [who, key, revision]
+        ConfigUpdated(AccountId, ConfigKey, u32),
+        /// Roles allowed to change the configuration key have been set [key, roles]
+        ConfigWritersSet(ConfigKey, AccountRole),
+        /// Limit of the free calls has been set or removed for the roles [roles, limit]
+        RateLimitSet(AccountRole, Option<RateLimit<BlockNumber>>),
         // add other events here
     }
 );
@@ -165,6 +597,42 @@ decl_error! {
         NotAuthorized,
         /// Account doesn't exist
         NotExists,
+        /// Metadata field exceeds `MaxMetadataLen`
+        MetadataTooLong,
+        /// Account has no metadata
+        NoMetadata,
+        /// Account has a pending role request already
+        AlreadyRequested,
+        /// Account has no pending role request
+        NoRequest,
+        /// Batch exceeds `MaxBatchSize`
+        BatchTooLarge,
+        /// Delegate has no delegation of the admin or the permission
+        NotDelegated,
+        /// Delegation has expired
+        DelegationExpired,
+        /// Admin can't disable their own account or drop their own ADMIN role
+        CannotDisableSelf,
+        /// Role contains unknown role bits
+        UnknownRole,
+        /// Admin has exactly these roles already
+        AlreadyAdmin,
+        /// Free balance is lower than the amount to lock
+        InsufficientBalanceForLock,
+        /// Account has no lock of the category
+        LockNotFound,
+        /// Account has `MaxLocks` locks already
+        TooManyLocks,
+        /// Account isn't enabled in the registry
+        NotRegistered,
+        /// Escrow doesn't exist
+        NoEscrow,
+        /// Release condition of the escrow isn't met
+        EscrowLocked,
+        /// Configuration key or value exceeds `MaxConfigLen`
+        ConfigTooLong,
+        /// Account has no balance to lock
+        NoBalance,
         // add additional errors below
     }
 }
@@ -178,11 +646,37 @@ decl_module! {
         type Error = Error<T>;
 
         // Make module constants visible in Node's metadata
-        const AdminRole: T::AccountRole = T::AdminRole::get();
+        const AdminRole: T::AccountRole = T::AccountRole::ADMIN;
+        const StaleAccountAge: T::Moment = T::StaleAccountAge::get();
+        const ReportInterval: T::BlockNumber = T::ReportInterval::get();
+        const MaxMetadataLen: u32 = T::MaxMetadataLen::get();
+        const MetadataDepositBase: BalanceOf<T> = T::MetadataDepositBase::get();
+        const MetadataDepositPerByte: BalanceOf<T> = T::MetadataDepositPerByte::get();
+        const RoleRequestDeposit: BalanceOf<T> = T::RoleRequestDeposit::get();
+        const AdminDeposit: BalanceOf<T> = T::AdminDeposit::get();
+        const UserDeposit: BalanceOf<T> = T::UserDeposit::get();
+        const MaxLocks: u32 = T::MaxLocks::get();
+        const MaxConfigLen: u32 = T::MaxConfigLen::get();
+        const MaxConfigHistory: u32 = T::MaxConfigHistory::get();
+        const MaxBatchSize: u32 = T::MaxBatchSize::get();
+        const ReapPolicy: ReapPolicy = T::ReapPolicy::get();
 
         // Events must be initialized if they are used by the pallet.
         fn deposit_event() = default;
 
+        /// Move `Something` value into the configuration store.
+        fn on_runtime_upgrade() -> Weight {
+            let something: Option<u32> =
+                migration::take_storage_value(b"TemplateModule", b"Something", &[]);
+            match something {
+                Some(something) => {
+                    ConfigValues::insert(SOMETHING_KEY.to_vec(), something.encode());
+                    T::DbWeight::get().reads_writes(1, 2)
+                }
+                None => T::DbWeight::get().reads(1),
+            }
+        }
+
         /// Create or update an entry in account registry with specific role.
         #[weight = <T as Trait>::WeightInfo::account_add()]
         pub fn account_add(origin, account: T::AccountId, role: T::AccountRole) -> dispatch::DispatchResult {
@@ -190,18 +684,13 @@ decl_module! {
             // This function will return an error if the extrinsic is not signed.
             // https://substrate.dev/docs/en/knowledgebase/runtime/origin
             let who = ensure_signed(origin)?;
-            ensure!(AccountOf::<T>::is_role_correct(role), Error::<T>::InvalidData);
             ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
+            Self::ensure_role_grant(&account, role)?;
+            Self::ensure_keeps_admin(&who, &account, role)?;
+            Self::reserve_deposit(&who, &account, role)?;
 
             // Update storage.
-            AccountRegistry::<T>::mutate(&account, |acc|{
-                debug::info!("account_add: roles={:?} create_time={:?}", acc.roles, acc.create_time);
-                acc.roles = role;
-                if acc.create_time.is_zero(){
-                    // Get current timestamp using pallet-timestamp module
-                    acc.create_time = <pallet_timestamp::Module<T>>::get();
-                }
-            });
+            Self::set_account_role(&account, role);
 
             // Emit an event.
             Self::deposit_event(RawEvent::AccountCreated(who, account, role));
@@ -225,19 +714,419 @@ decl_module! {
             // Ensure origin has associated account with admin privileges.
             ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
             // Self disabling is prohibited.
-            ensure!(who != whom, Error::<T>::InvalidAction);
+            ensure!(who != whom, Error::<T>::CannotDisableSelf);
             // Raise error if the account doesn't exist or has been disabled already.
             ensure!(AccountRegistry::<T>::contains_key(&whom), Error::<T>::NotExists);
             // Delete a value from storage.
-            AccountRegistry::<T>::remove(&whom);
+            Self::remove_account(&whom);
             // or just disable it by assigning 0 value to the role
             // AccountRegistry::<T>::mutate(&whom, |item|{
-            // 	item.roles = NONE_ROLE;
+            // 	item.roles = T::AccountRole::default();
             // });
             Self::deposit_event(RawEvent::AccountDisabled(who, whom));
             Ok(())
         }
 
+        /// Allow `delegate` to call registry dispatchables on behalf of the admin
+        /// with the given `permissions` until `expiry` block. Replaces the previous delegation.
+        #[weight = <T as Trait>::WeightInfo::add_delegate()]
+        pub fn add_delegate(
+            origin,
+            delegate: T::AccountId,
+            permissions: u8,
+            expiry: T::BlockNumber,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
+            ensure!(
+                permissions != 0 && permissions & !ALL_PERMISSIONS == 0,
+                Error::<T>::InvalidData
+            );
+            ensure!(expiry > <frame_system::Module<T>>::block_number(), Error::<T>::InvalidData);
+
+            Delegations::<T>::insert(&who, &delegate, Delegation { permissions, expiry });
+
+            Self::deposit_event(RawEvent::DelegateAdded(who, delegate, permissions, expiry));
+            Ok(())
+        }
+
+        /// Revoke the delegation.
+        #[weight = <T as Trait>::WeightInfo::remove_delegate()]
+        pub fn remove_delegate(origin, delegate: T::AccountId) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Delegations::<T>::contains_key(&who, &delegate), Error::<T>::NotDelegated);
+
+            Delegations::<T>::remove(&who, &delegate);
+
+            Self::deposit_event(RawEvent::DelegateRemoved(who, delegate));
+            Ok(())
+        }
+
+        /// `account_add` called by a delegate on behalf of the `admin`.
+        /// ADMIN role can't be granted by delegates.
+        #[weight = <T as Trait>::WeightInfo::delegated_account_add()]
+        pub fn delegated_account_add(
+            origin,
+            admin: T::AccountId,
+            account: T::AccountId,
+            role: T::AccountRole,
+        ) -> dispatch::DispatchResult {
+            let delegate = ensure_signed(origin)?;
+            Self::ensure_delegate(&admin, &delegate, PERMISSION_ACCOUNT_ADD)?;
+            ensure!(!role.contains(T::AccountRole::ADMIN), Error::<T>::NotAuthorized);
+            Self::ensure_role_grant(&account, role)?;
+            Self::ensure_keeps_admin(&admin, &account, role)?;
+            Self::reserve_deposit(&admin, &account, role)?;
+
+            Self::set_account_role(&account, role);
+
+            Self::deposit_event(RawEvent::AccountCreated(admin.clone(), account, role));
+            Self::deposit_event(RawEvent::DelegateActed(admin, delegate));
+            Ok(())
+        }
+
+        /// `account_disable` called by a delegate on behalf of the `admin`.
+        #[weight = <T as Trait>::WeightInfo::delegated_account_disable()]
+        pub fn delegated_account_disable(
+            origin,
+            admin: T::AccountId,
+            whom: T::AccountId,
+        ) -> dispatch::DispatchResult {
+            let delegate = ensure_signed(origin)?;
+            Self::ensure_delegate(&admin, &delegate, PERMISSION_ACCOUNT_DISABLE)?;
+            ensure!(admin != whom, Error::<T>::CannotDisableSelf);
+            ensure!(AccountRegistry::<T>::contains_key(&whom), Error::<T>::NotExists);
+
+            Self::remove_account(&whom);
+
+            Self::deposit_event(RawEvent::AccountDisabled(admin.clone(), whom));
+            Self::deposit_event(RawEvent::DelegateActed(admin, delegate));
+            Ok(())
+        }
+
+        /// Create or update entries of account registry in a single call.
+        /// Either all the accounts are added or none of them.
+        #[weight = <T as Trait>::WeightInfo::account_add_batch(accounts.len() as u32)]
+        pub fn account_add_batch(
+            origin,
+            accounts: Vec<(T::AccountId, T::AccountRole)>,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
+            ensure!(accounts.len() as u32 <= T::MaxBatchSize::get(), Error::<T>::BatchTooLarge);
+            Self::add_accounts(&who, accounts)
+        }
+
+        /// Disable entries of account registry in a single call.
+        /// Either all the accounts are disabled or none of them.
+        #[weight = <T as Trait>::WeightInfo::account_disable_batch(accounts.len() as u32)]
+        pub fn account_disable_batch(origin, accounts: Vec<T::AccountId>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
+            ensure!(accounts.len() as u32 <= T::MaxBatchSize::get(), Error::<T>::BatchTooLarge);
+            Self::disable_accounts(&who, accounts)
+        }
+
+        /// Attach metadata to the account of the registry.
+        /// Deposit `MetadataDepositBase + MetadataDepositPerByte * metadata length`
+        /// is reserved from the caller. Deposit of the replaced metadata is refunded.
+        #[weight = <T as Trait>::WeightInfo::account_set_metadata()]
+        pub fn account_set_metadata(
+            origin,
+            account: T::AccountId,
+            metadata: AccountMetadata<T::Hash>,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
+            ensure!(AccountRegistry::<T>::contains_key(&account), Error::<T>::NotExists);
+            let max_len = T::MaxMetadataLen::get() as usize;
+            ensure!(
+                metadata.name.len() <= max_len && metadata.external_id.len() <= max_len,
+                Error::<T>::MetadataTooLong
+            );
+
+            let deposit = T::MetadataDepositPerByte::get()
+                .saturating_mul(BalanceOf::<T>::from(metadata.deposit_len() as u32))
+                .saturating_add(T::MetadataDepositBase::get());
+            T::Currency::reserve(&who, deposit)?;
+            if let Some(old) = Metadata::<T>::get(&account) {
+                T::Currency::unreserve(&old.depositor, old.deposit);
+            }
+            Metadata::<T>::insert(&account, MetadataRecord {
+                metadata,
+                depositor: who.clone(),
+                deposit,
+            });
+
+            Self::deposit_event(RawEvent::MetadataSet(who, account, deposit));
+            Ok(())
+        }
+
+        /// Remove metadata of the account and refund the deposit.
+        #[weight = <T as Trait>::WeightInfo::account_clear_metadata()]
+        pub fn account_clear_metadata(origin, account: T::AccountId) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
+            ensure!(Metadata::<T>::contains_key(&account), Error::<T>::NoMetadata);
+            Self::remove_metadata(&account);
+            Ok(())
+        }
+
+        /// Ask admins for a role. `RoleRequestDeposit` is reserved until the request is decided.
+        /// `note` is limited by `MaxMetadataLen`.
+        #[weight = <T as Trait>::WeightInfo::request_role()]
+        pub fn request_role(origin, role: T::AccountRole, note: Vec<u8>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(AccountOf::<T>::is_role_correct(role) && !role.is_empty(), Error::<T>::UnknownRole);
+            Self::ensure_role_grant(&who, role)?;
+            ensure!(note.len() <= T::MaxMetadataLen::get() as usize, Error::<T>::MetadataTooLong);
+            ensure!(!PendingRequests::<T>::contains_key(&who), Error::<T>::AlreadyRequested);
+
+            let deposit = T::RoleRequestDeposit::get();
+            T::Currency::reserve(&who, deposit)?;
+            PendingRequests::<T>::insert(&who, RoleRequest {
+                role,
+                note,
+                deposit,
+                create_time: <pallet_timestamp::Module<T>>::get(),
+            });
+
+            Self::deposit_event(RawEvent::RoleRequested(who, role, deposit));
+            Ok(())
+        }
+
+        /// Grant the requested role and refund the deposit.
+        /// Admin can't approve their own request dropping the ADMIN role.
+        #[weight = <T as Trait>::WeightInfo::approve_request()]
+        pub fn approve_request(origin, account: T::AccountId) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
+            let request = PendingRequests::<T>::get(&account).ok_or(Error::<T>::NoRequest)?;
+            Self::ensure_keeps_admin(&who, &account, request.role)?;
+            Self::reserve_deposit(&who, &account, request.role)?;
+
+            PendingRequests::<T>::remove(&account);
+            T::Currency::unreserve(&account, request.deposit);
+            Self::set_account_role(&account, request.role);
+
+            Self::deposit_event(RawEvent::RoleRequestApproved(who.clone(), account.clone(), request.role));
+            Self::deposit_event(RawEvent::AccountCreated(who, account, request.role));
+            Ok(())
+        }
+
+        /// Reject the request and slash the deposit.
+        #[weight = <T as Trait>::WeightInfo::reject_request()]
+        pub fn reject_request(origin, account: T::AccountId) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
+            let request = PendingRequests::<T>::take(&account).ok_or(Error::<T>::NoRequest)?;
+
+            // Slashed funds are burned
+            let (_, not_slashed) = T::Currency::slash_reserved(&account, request.deposit);
+
+            Self::deposit_event(RawEvent::RoleRequestRejected(
+                who,
+                account,
+                request.deposit.saturating_sub(not_slashed),
+            ));
+            Ok(())
+        }
+
+        /// Create or update an entry in account registry by `AdminOrigin`.
+        /// Allows to recover the registry if no admin account is available.
+        #[weight = <T as Trait>::WeightInfo::force_account_add()]
+        pub fn force_account_add(origin, account: T::AccountId, role: T::AccountRole) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(AccountOf::<T>::is_role_correct(role), Error::<T>::UnknownRole);
+
+            Self::set_account_role(&account, role);
+
+            Self::deposit_event(RawEvent::AccountForceCreated(account, role));
+            Ok(())
+        }
+
+        /// Remove an entry from account registry by `AdminOrigin`.
+        #[weight = <T as Trait>::WeightInfo::force_account_disable()]
+        pub fn force_account_disable(origin, account: T::AccountId) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(AccountRegistry::<T>::contains_key(&account), Error::<T>::NotExists);
+
+            Self::remove_account(&account);
+
+            Self::deposit_event(RawEvent::AccountForceDisabled(account));
+            Ok(())
+        }
+
+        /// Remove all the registry locks of the account by `AdminOrigin`.
+        #[weight = <T as Trait>::WeightInfo::force_unlock(T::MaxLocks::get())]
+        pub fn force_unlock(origin, account: T::AccountId) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            let locks = Self::account_locks(&account);
+            ensure!(!locks.is_empty(), Error::<T>::LockNotFound);
+
+            for (id, _) in locks {
+                T::Currency::remove_lock(id, &account);
+            }
+            AccountLocks::<T>::remove_prefix(&account);
+
+            Self::deposit_event(RawEvent::BalanceUnlocked(account));
+            Ok(())
+        }
+
+        /// Lock `amount` of the account free balance under the category `id`.
+        /// The lock of the same category is replaced.
+        #[weight = <T as Trait>::WeightInfo::set_lock()]
+        pub fn set_lock(
+            origin,
+            account: T::AccountId,
+            id: LockIdentifier,
+            amount: BalanceOf<T>,
+            reasons: LockReasons,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
+            // Balances reaps the account without balance, dropping the locks of the registry
+            ensure!(!T::Currency::total_balance(&account).is_zero(), Error::<T>::NoBalance);
+            ensure!(
+                amount <= T::Currency::free_balance(&account),
+                Error::<T>::InsufficientBalanceForLock
+            );
+            Self::ensure_can_lock(&account, id)?;
+
+            Self::set_account_lock(&who, &account, id, amount, reasons);
+
+            Self::deposit_event(RawEvent::LockSet(who, account, id, amount));
+            Ok(())
+        }
+
+        /// Remove the lock of the category `id` from the account.
+        #[weight = <T as Trait>::WeightInfo::remove_lock()]
+        pub fn remove_lock(origin, account: T::AccountId, id: LockIdentifier) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
+            AccountLocks::<T>::take(&account, id).ok_or(Error::<T>::LockNotFound)?;
+
+            T::Currency::remove_lock(id, &account);
+
+            Self::deposit_event(RawEvent::LockRemoved(who, account, id));
+            Ok(())
+        }
+
+        /// Reserve `amount` from the sender until it is released to the `counterparty`
+        /// according to the `condition`. Both accounts have to be enabled in the registry.
+        /// The amount is at least the existential deposit, so it can be released to a new account.
+        #[weight = <T as Trait>::WeightInfo::create_escrow()]
+        pub fn create_escrow(
+            origin,
+            counterparty: T::AccountId,
+            amount: BalanceOf<T>,
+            condition: ReleaseCondition<T::BlockNumber>,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(AccountRegistry::<T>::get(&who).is_enable(), Error::<T>::NotRegistered);
+            ensure!(AccountRegistry::<T>::get(&counterparty).is_enable(), Error::<T>::NotRegistered);
+            ensure!(
+                who != counterparty && amount >= T::Currency::minimum_balance(),
+                Error::<T>::InvalidData
+            );
+
+            T::Currency::reserve(&who, amount)?;
+            let id = NextEscrowId::mutate(|id| {
+                let current = *id;
+                *id = id.wrapping_add(1);
+                current
+            });
+            Escrows::<T>::insert(id, Escrow {
+                creator: who.clone(),
+                counterparty: counterparty.clone(),
+                amount,
+                condition,
+                approvals: Vec::new(),
+            });
+
+            Self::deposit_event(RawEvent::EscrowCreated(id, who, counterparty, amount));
+            Ok(())
+        }
+
+        /// Release the escrowed funds to the counterparty if the release condition is met.
+        /// With `BothParties` condition records the approval of the creator or the counterparty.
+        #[weight = <T as Trait>::WeightInfo::release_escrow()]
+        pub fn release_escrow(origin, id: EscrowId) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let mut escrow = Escrows::<T>::get(id).ok_or(Error::<T>::NoEscrow)?;
+
+            match escrow.condition {
+                ReleaseCondition::After(block) => ensure!(
+                    <frame_system::Module<T>>::block_number() >= block,
+                    Error::<T>::EscrowLocked
+                ),
+                ReleaseCondition::AdminApproval => {
+                    ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized)
+                }
+                ReleaseCondition::BothParties => {
+                    ensure!(
+                        who == escrow.creator || who == escrow.counterparty,
+                        Error::<T>::NotAuthorized
+                    );
+                    if !escrow.approvals.contains(&who) {
+                        escrow.approvals.push(who.clone());
+                        Self::deposit_event(RawEvent::EscrowApproved(id, who));
+                    }
+                    if escrow.approvals.len() < 2 {
+                        Escrows::<T>::insert(id, escrow);
+                        return Ok(());
+                    }
+                }
+            }
+
+            Escrows::<T>::remove(id);
+            // Funds slashed from the reserve meanwhile can't be released.
+            // The escrow is above the existential deposit, so the counterparty is created if needed.
+            let (released, _) = T::Currency::slash_reserved(&escrow.creator, escrow.amount);
+            let amount = released.peek();
+            T::Currency::resolve_creating(&escrow.counterparty, released);
+
+            Self::deposit_event(RawEvent::EscrowReleased(id, escrow.counterparty, amount));
+            Ok(())
+        }
+
+        /// Cancel the escrow and refund the creator.
+        /// Allowed to the counterparty and to the registry admins.
+        #[weight = <T as Trait>::WeightInfo::cancel_escrow()]
+        pub fn cancel_escrow(origin, id: EscrowId) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let escrow = Escrows::<T>::get(id).ok_or(Error::<T>::NoEscrow)?;
+            ensure!(
+                who == escrow.counterparty || Self::account_is_admin(&who),
+                Error::<T>::NotAuthorized
+            );
+
+            Escrows::<T>::remove(id);
+            T::Currency::unreserve(&escrow.creator, escrow.amount);
+
+            Self::deposit_event(RawEvent::EscrowCancelled(id, who));
+            Ok(())
+        }
+
+        /// Dispatch the call with `Root` origin by `AdminOrigin`,
+        /// e.g. runtime upgrade approved by the admins council.
+        #[weight = {
+            let dispatch_info = call.get_dispatch_info();
+            (
+                <T as Trait>::WeightInfo::root_dispatch().saturating_add(dispatch_info.weight),
+                dispatch_info.class,
+            )
+        }]
+        #[allow(clippy::boxed_local)]
+        pub fn root_dispatch(origin, call: Box<<T as Trait>::Call>) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            let result = call.dispatch_bypass_filter(RawOrigin::Root.into());
+
+            Self::deposit_event(RawEvent::RootDispatched(result.map(|_| ()).map_err(|e| e.error)));
+            Ok(())
+        }
+
         /// An example dispatchable that demonstrates `pallet_balances` capability to  froze
         /// account balance for specific purpose.
         /// After `account_transfer_and_lock` was called the account can be put his balance only to pay off fees.
@@ -246,15 +1135,15 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             // Ensure origin has associated account with admin privileges.
             ensure!(Self::account_is_admin(&sender), Error::<T>::NotAuthorized);
+            ensure!(
+                T::Currency::free_balance(&sender) >= amount,
+                Error::<T>::InsufficientBalanceForLock
+            );
+            Self::ensure_can_lock(&whom, FEE_LOCK_ID)?;
 
             T::Currency::transfer(&sender, &whom, amount, AllowDeath)?;
             let amount = T::Currency::free_balance(&whom);
-            T::Currency::set_lock(
-                FEE_LOCK_ID,
-                &whom,
-                amount,
-                WithdrawReasons::except(WithdrawReason::TransactionPayment),
-            );
+            Self::set_account_lock(&sender, &whom, FEE_LOCK_ID, amount, LockReasons::ExceptFee);
             Self::deposit_event(RawEvent::BalanceLocked(whom, amount));
 
             Ok(())
@@ -263,6 +1152,8 @@ decl_module! {
         /// An example dispatchable that takes a singles value as a parameter, writes the value to
         /// storage and emits an event. This function must be dispatched by a signed extrinsic.
         /// Origin doesn't pay fee for this transaction and can call it with zero balance.
+        /// Any signed origin changes `SOMETHING_KEY` configuration, free calls are limited by
+        /// `CheckRateLimit` instead of the config writers.
         #[weight = (10_000_000, DispatchClass::Normal, Pays::No)]
         pub fn do_something(origin, something: u32) -> dispatch::DispatchResult {
             // Check that the extrinsic was signed and get the signer.
@@ -271,7 +1162,7 @@ decl_module! {
             let who = ensure_signed(origin)?;
 
             // Update storage.
-            Something::put(something);
+            Self::write_config(&who, SOMETHING_KEY.to_vec(), Some(something.encode()));
             debug::info!("do_something: who={:?} what={:?}", who, something);
             // Emit an event.
             Self::deposit_event(RawEvent::SomethingStored(something, who));
@@ -286,35 +1177,615 @@ decl_module! {
         #[weight = <T as Trait>::WeightInfo::update_something()]
         pub fn update_something(origin, something: u32) -> dispatch::DispatchResultWithPostInfo{
             let who = ensure_signed(origin)?;
-            ensure!(Self::account_is_admin(&who), Error::<T>::NotAuthorized);
-            Something::try_mutate(|v|->dispatch::DispatchResultWithPostInfo{
-                let res = match v {
-                    // disable pay
-                    Some(ref prev) if *prev>something => (Pays::No).into(),
-                    // default weight and pay value
-                    _ => None.into(),
-                };
-                *v = Some(something);
-                // Event emission should be perform after the storage has been updated.
-                // Here we can ensure that update will succeed.
-                Self::deposit_event(RawEvent::SomethingStored(something, who));
-                Ok(res)
-            })
+            Self::ensure_config_writer(&who, SOMETHING_KEY)?;
+            let res = match Self::something() {
+                // disable pay
+                Some(prev) if prev > something => (Pays::No).into(),
+                // default weight and pay value
+                _ => None.into(),
+            };
+            Self::write_config(&who, SOMETHING_KEY.to_vec(), Some(something.encode()));
+            // Event emission should be perform after the storage has been updated.
+            // Here we can ensure that update will succeed.
+            Self::deposit_event(RawEvent::SomethingStored(something, who));
+            Ok(res)
+        }
+
+        /// Set the configuration value of the key, `None` removes the key.
+        /// The sender has to have one of the roles allowed to change the key.
+        #[weight = <T as Trait>::WeightInfo::set_config()]
+        pub fn set_config(origin, key: ConfigKey, value: Option<Vec<u8>>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let max_len = T::MaxConfigLen::get() as usize;
+            ensure!(
+                key.len() <= max_len && value.as_ref().map_or(true, |v| v.len() <= max_len),
+                Error::<T>::ConfigTooLong
+            );
+            Self::ensure_config_writer(&who, &key)?;
+
+            Self::write_config(&who, key, value);
+            Ok(())
+        }
+
+        /// Set the roles allowed to change the configuration key by `AdminOrigin`.
+        /// Empty roles leave the key to the admins.
+        #[weight = <T as Trait>::WeightInfo::set_config_writers()]
+        pub fn set_config_writers(origin, key: ConfigKey, roles: T::AccountRole) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(key.len() <= T::MaxConfigLen::get() as usize, Error::<T>::ConfigTooLong);
+            ensure!(AccountOf::<T>::is_role_correct(roles), Error::<T>::UnknownRole);
+
+            if roles.is_empty() {
+                ConfigWriters::<T>::remove(&key);
+            } else {
+                ConfigWriters::<T>::insert(&key, roles);
+            }
+
+            Self::deposit_event(RawEvent::ConfigWritersSet(key, roles));
+            Ok(())
+        }
+
+        /// Set the limit of the free calls of the accounts having the roles by `AdminOrigin`.
+        /// `None` removes the limit.
+        #[weight = <T as Trait>::WeightInfo::set_rate_limit()]
+        pub fn set_rate_limit(origin, roles: T::AccountRole, limit: Option<RateLimitOf<T>>) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(AccountOf::<T>::is_role_correct(roles), Error::<T>::UnknownRole);
+            ensure!(
+                limit.map_or(true, |limit| !limit.period.is_zero()),
+                Error::<T>::InvalidData
+            );
+
+            match limit {
+                Some(limit) => RateLimits::<T>::insert(roles, limit),
+                None => RateLimits::<T>::remove(roles),
+            }
+
+            Self::deposit_event(RawEvent::RateLimitSet(roles, limit));
+            Ok(())
+        }
+
+        /// Flag accounts reported by the offchain worker as stale.
+        /// The report is validated by `ValidateUnsigned` implementation.
+        #[weight = <T as Trait>::WeightInfo::report_stale_accounts(payload.accounts.len() as u32)]
+        pub fn report_stale_accounts(
+            origin,
+            payload: StaleAccountsPayloadOf<T>,
+            _signature: T::Signature,
+        ) -> dispatch::DispatchResult {
+            ensure_none(origin)?;
+            let now = <pallet_timestamp::Module<T>>::get();
+            for account in payload.accounts {
+                // Skip accounts changed since the report has been built
+                if StaleAccounts::<T>::contains_key(&account)
+                    || !Self::is_stale(&AccountRegistry::<T>::get(&account), now)
+                {
+                    continue;
+                }
+                StaleAccounts::<T>::insert(&account, now);
+                Self::deposit_event(RawEvent::AccountFlagged(account));
+            }
+            NextReportAt::<T>::put(payload.block_number + T::ReportInterval::get());
+            Ok(())
+        }
+
+        /// Look for stale accounts in the registry and report them with unsigned transaction
+        /// signed by the local admin key.
+        fn offchain_worker(block_number: T::BlockNumber) {
+            if let Err(e) = Self::offchain_report_stale_accounts(block_number) {
+                debug::warn!("offchain_worker: {}", e);
+            }
         }
     }
 }
 // Module allows  use  common functionality by dispatchables
 impl<T: Trait> Module<T> {
+    /// Value stored by `do_something` and `update_something`.
+    pub fn something() -> Option<u32> {
+        ConfigValues::get(SOMETHING_KEY).and_then(|value| u32::decode(&mut &value[..]).ok())
+    }
+
+    /// Limit of the free calls of the account, the widest of the limits of its roles.
+    /// `None` if the account isn't limited.
+    pub fn account_rate_limit(who: &T::AccountId) -> Option<RateLimitOf<T>> {
+        let roles = AccountRegistry::<T>::get(who).roles;
+        RateLimits::<T>::iter()
+            .filter(|(limit_roles, _)| roles.contains(*limit_roles))
+            .map(|(_, limit)| limit)
+            .max_by(|a, b| a.cmp_rate(b))
+    }
+
+    /// Usage of the rate limit after a free call of the account, `None` if the account
+    /// isn't limited.
+    fn rate_limit_call(
+        who: &T::AccountId,
+    ) -> Result<Option<(T::BlockNumber, u32)>, TransactionValidityError> {
+        let limit = match Self::account_rate_limit(who) {
+            Some(limit) => limit,
+            None => return Ok(None),
+        };
+        let now = <frame_system::Module<T>>::block_number();
+        let (start, calls) = match RateLimitUsage::<T>::get(who) {
+            (start, _) if now >= start.saturating_add(limit.period) => (now, 0),
+            usage => usage,
+        };
+        if calls >= limit.calls {
+            return Err(InvalidTransaction::Custom(RATE_LIMIT_EXCEEDED).into());
+        }
+        Ok(Some((start, calls + 1)))
+    }
+
+    /// Check that the account has one of the roles allowed to change the configuration key.
+    fn ensure_config_writer(who: &T::AccountId, key: &[u8]) -> DispatchResult {
+        let writers = ConfigWriters::<T>::get(key).unwrap_or(T::AccountRole::ADMIN);
+        ensure!(
+            !AccountRegistry::<T>::get(who)
+                .roles
+                .intersection(writers)
+                .is_empty(),
+            Error::<T>::NotAuthorized
+        );
+        Ok(())
+    }
+
+    /// Change the configuration value and record the change in the history.
+    fn write_config(who: &T::AccountId, key: ConfigKey, value: Option<Vec<u8>>) {
+        match &value {
+            Some(value) => ConfigValues::insert(&key, value),
+            None => ConfigValues::remove(&key),
+        }
+        let revision = ConfigRevision::mutate(&key, |revision| {
+            *revision = revision.saturating_add(1);
+            *revision
+        });
+        ConfigHistory::<T>::insert(
+            &key,
+            revision,
+            ConfigChange {
+                value,
+                who: who.clone(),
+                block_number: <frame_system::Module<T>>::block_number(),
+            },
+        );
+        if let Some(expired) = revision.checked_sub(T::MaxConfigHistory::get()) {
+            ConfigHistory::<T>::remove(&key, expired);
+        }
+        Self::deposit_event(RawEvent::ConfigUpdated(who.clone(), key, revision));
+    }
+
     // Implement module function.
     // Public functions can be called from other runtime modules.
     /// Check if an account has ADMIN role
     pub fn account_is_admin(acc: &T::AccountId) -> bool {
         AccountRegistry::<T>::get(acc).is_admin()
     }
+
+    /// Create or update the registry entry with the given role.
+    fn set_account_role(account: &T::AccountId, role: T::AccountRole) {
+        // Stale account is renewed by the admin
+        let was_stale = StaleAccounts::<T>::take(account).is_some();
+        AccountRegistry::<T>::mutate(account, |acc| {
+            debug::info!(
+                "set_account_role: roles={:?} create_time={:?}",
+                acc.roles,
+                acc.create_time
+            );
+            acc.roles = role;
+            if acc.create_time.is_zero() || was_stale {
+                // Get current timestamp using pallet-timestamp module
+                acc.create_time = <pallet_timestamp::Module<T>>::get();
+            }
+        });
+        Self::update_admins(account, AccountRegistry::<T>::get(account).is_admin());
+    }
+
+    /// Keep `Admins` sorted and notify `AdminsChanged` if ADMIN role of the account has changed.
+    fn update_admins(account: &T::AccountId, is_admin: bool) {
+        let mut admins = Admins::<T>::get();
+        match (admins.binary_search(account), is_admin) {
+            (Err(index), true) => {
+                admins.insert(index, account.clone());
+                T::AdminsChanged::change_members_sorted(&[account.clone()], &[], &admins);
+            }
+            (Ok(index), false) => {
+                admins.remove(index);
+                T::AdminsChanged::change_members_sorted(&[], &[account.clone()], &admins);
+            }
+            _ => return,
+        }
+        Admins::<T>::put(admins);
+    }
+
+    /// Build `Admins` from the registry and pass them to `AdminsChanged`.
+    /// Used by the runtime upgrade introducing `Admins` storage.
+    pub fn initialize_admins() -> Weight {
+        let mut admins: Vec<_> = AccountRegistry::<T>::iter()
+            .filter(|(_, acc)| acc.is_admin())
+            .map(|(who, _)| who)
+            .collect();
+        admins.sort();
+        let old = Admins::<T>::get();
+        T::AdminsChanged::set_members_sorted(&admins, &old);
+        Admins::<T>::put(admins);
+
+        let accounts = AccountRegistry::<T>::iter().count() as Weight;
+        T::DbWeight::get().reads_writes(accounts + 1, 1)
+    }
+
+    /// Remove the registry entry with the related data.
+    fn remove_account(account: &T::AccountId) {
+        AccountRegistry::<T>::remove(account);
+        Self::update_admins(account, false);
+        Delegations::<T>::remove_prefix(account);
+        StaleAccounts::<T>::remove(account);
+        Self::remove_metadata(account);
+        Self::release_deposit(account);
+    }
+
+    /// Check that `delegate` is allowed to act on behalf of the admin with the permission.
+    pub fn ensure_delegate(
+        admin: &T::AccountId,
+        delegate: &T::AccountId,
+        permission: u8,
+    ) -> dispatch::DispatchResult {
+        ensure!(Self::account_is_admin(admin), Error::<T>::NotAuthorized);
+        let delegation = Delegations::<T>::get(admin, delegate).ok_or(Error::<T>::NotDelegated)?;
+        ensure!(
+            delegation.permissions & permission == permission,
+            Error::<T>::NotDelegated
+        );
+        ensure!(
+            delegation.expiry > <frame_system::Module<T>>::block_number(),
+            Error::<T>::DelegationExpired
+        );
+        Ok(())
+    }
+
+    /// Add the accounts of the batch, storage changes are reverted if any of them fails.
+    #[transactional]
+    fn add_accounts(
+        who: &T::AccountId,
+        accounts: Vec<(T::AccountId, T::AccountRole)>,
+    ) -> DispatchResult {
+        accounts.into_iter().try_for_each(|(account, role)| {
+            Self::ensure_role_grant(&account, role)?;
+            Self::ensure_keeps_admin(who, &account, role)?;
+            Self::reserve_deposit(who, &account, role)?;
+            Self::set_account_role(&account, role);
+            Self::deposit_event(RawEvent::AccountCreated(who.clone(), account, role));
+            Ok(())
+        })
+    }
+
+    /// Disable the accounts of the batch, storage changes are reverted if any of them fails.
+    #[transactional]
+    fn disable_accounts(who: &T::AccountId, accounts: Vec<T::AccountId>) -> DispatchResult {
+        accounts.into_iter().try_for_each(|whom| {
+            ensure!(*who != whom, Error::<T>::CannotDisableSelf);
+            ensure!(
+                AccountRegistry::<T>::contains_key(&whom),
+                Error::<T>::NotExists
+            );
+            Self::remove_account(&whom);
+            Self::deposit_event(RawEvent::AccountDisabled(who.clone(), whom));
+            Ok(())
+        })
+    }
+
+    /// Check that the role is known and doesn't grant the same roles to an admin again.
+    /// Admin roles can still be updated as long as they change.
+    fn ensure_role_grant(account: &T::AccountId, role: T::AccountRole) -> DispatchResult {
+        ensure!(
+            AccountOf::<T>::is_role_correct(role),
+            Error::<T>::UnknownRole
+        );
+        let acc = AccountRegistry::<T>::get(account);
+        ensure!(
+            !(acc.is_admin() && acc.roles == role),
+            Error::<T>::AlreadyAdmin
+        );
+        Ok(())
+    }
+
+    /// Check that the admin doesn't drop their own ADMIN role, so the registry keeps an admin.
+    fn ensure_keeps_admin(
+        admin: &T::AccountId,
+        account: &T::AccountId,
+        role: T::AccountRole,
+    ) -> DispatchResult {
+        ensure!(
+            admin != account || role.contains(T::AccountRole::ADMIN),
+            Error::<T>::CannotDisableSelf
+        );
+        Ok(())
+    }
+
+    /// Locks of the account by category.
+    pub fn account_locks(account: &T::AccountId) -> Vec<(LockIdentifier, LockInfoOf<T>)> {
+        AccountLocks::<T>::iter_prefix(account).collect()
+    }
+
+    /// Check that a new lock category doesn't exceed `MaxLocks` of the account.
+    fn ensure_can_lock(account: &T::AccountId, id: LockIdentifier) -> DispatchResult {
+        ensure!(
+            AccountLocks::<T>::contains_key(account, id)
+                || (AccountLocks::<T>::iter_prefix(account).count() as u32) < T::MaxLocks::get(),
+            Error::<T>::TooManyLocks
+        );
+        Ok(())
+    }
+
+    fn set_account_lock(
+        creator: &T::AccountId,
+        account: &T::AccountId,
+        id: LockIdentifier,
+        amount: BalanceOf<T>,
+        reasons: LockReasons,
+    ) {
+        // Balances ignores zero locks, keeping the previous lock of the category
+        if amount.is_zero() {
+            T::Currency::remove_lock(id, account);
+        } else {
+            T::Currency::set_lock(id, account, amount, reasons.into());
+        }
+        AccountLocks::<T>::insert(
+            account,
+            id,
+            LockInfo {
+                amount,
+                reasons,
+                creator: creator.clone(),
+            },
+        );
+    }
+
+    /// Registry entry deposit for the role.
+    fn role_deposit(role: T::AccountRole) -> BalanceOf<T> {
+        if role.is_empty() {
+            Zero::zero()
+        } else if role.contains(T::AccountRole::ADMIN) {
+            T::AdminDeposit::get()
+        } else {
+            T::UserDeposit::get()
+        }
+    }
+
+    /// Reserve the deposit for the new role of the account from the sponsor
+    /// and refund the previous one. Nothing is changed on failure.
+    fn reserve_deposit(
+        sponsor: &T::AccountId,
+        account: &T::AccountId,
+        role: T::AccountRole,
+    ) -> DispatchResult {
+        let amount = Self::role_deposit(role);
+        let previous = Deposits::<T>::get(account);
+        if previous.as_ref() == Some(&(sponsor.clone(), amount)) {
+            return Ok(());
+        }
+        if !amount.is_zero() {
+            T::Currency::reserve(sponsor, amount)?;
+        }
+        Self::release_deposit(account);
+        if !amount.is_zero() {
+            Deposits::<T>::insert(account, (sponsor.clone(), amount));
+            Self::deposit_event(RawEvent::DepositReserved(
+                sponsor.clone(),
+                account.clone(),
+                amount,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Refund the registry entry deposit of the account to the sponsor.
+    fn release_deposit(account: &T::AccountId) {
+        if let Some((sponsor, amount)) = Deposits::<T>::take(account) {
+            T::Currency::unreserve(&sponsor, amount);
+            Self::deposit_event(RawEvent::DepositUnreserved(
+                sponsor,
+                account.clone(),
+                amount,
+            ));
+        }
+    }
+
+    /// Remove metadata of the account and refund the deposit to the depositor.
+    fn remove_metadata(account: &T::AccountId) {
+        if let Some(record) = Metadata::<T>::take(account) {
+            T::Currency::unreserve(&record.depositor, record.deposit);
+            Self::deposit_event(RawEvent::MetadataCleared(
+                account.clone(),
+                record.depositor,
+                record.deposit,
+            ));
+        }
+    }
+
+    /// Check if enabled non-admin account has been created more than `StaleAccountAge` ago.
+    pub fn is_stale(acc: &AccountOf<T>, now: T::Moment) -> bool {
+        acc.is_enable() && !acc.is_admin() && acc.age(now) > T::StaleAccountAge::get()
+    }
+
+    fn offchain_report_stale_accounts(block_number: T::BlockNumber) -> Result<(), &'static str> {
+        if block_number < Self::next_report_at() {
+            return Ok(());
+        }
+        let now = <pallet_timestamp::Module<T>>::get();
+        let accounts: Vec<T::AccountId> = AccountRegistry::<T>::iter()
+            .filter(|(who, acc)| !StaleAccounts::<T>::contains_key(who) && Self::is_stale(acc, now))
+            .map(|(who, _)| who)
+            .take(MAX_STALE_ACCOUNTS_REPORT)
+            .collect();
+        if accounts.is_empty() {
+            return Ok(());
+        }
+
+        let (_, result) = Signer::<T, T::AuthorityId>::any_account()
+            .send_unsigned_transaction(
+                |account| StaleAccountsPayload {
+                    block_number,
+                    accounts: accounts.clone(),
+                    public: account.public.clone(),
+                },
+                Call::report_stale_accounts,
+            )
+            .ok_or("no local keys available")?;
+        result.map_err(|()| "unable to submit stale accounts report")
+    }
+}
+
+impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
+    type Call = Call<T>;
+
+    /// Accept stale accounts reports signed by registry admins once per `ReportInterval`.
+    fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+        let (payload, signature) = match call {
+            Call::report_stale_accounts(payload, signature) => (payload, signature),
+            _ => return InvalidTransaction::Call.into(),
+        };
+        if payload.accounts.is_empty() || payload.accounts.len() > MAX_STALE_ACCOUNTS_REPORT {
+            return InvalidTransaction::Call.into();
+        }
+        if payload.block_number < Self::next_report_at() {
+            return InvalidTransaction::Stale.into();
+        }
+        if payload.block_number > <frame_system::Module<T>>::block_number() {
+            return InvalidTransaction::Future.into();
+        }
+        if !Self::account_is_admin(&payload.public.clone().into_account()) {
+            return InvalidTransaction::BadProof.into();
+        }
+        if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
+            return InvalidTransaction::BadProof.into();
+        }
+
+        ValidTransaction::with_tag_prefix("TemplateModule")
+            .priority(T::UnsignedPriority::get())
+            // Single report per interval
+            .and_provides(Self::next_report_at())
+            .longevity(5)
+            .propagate(true)
+            .build()
+    }
+}
+
+/// Limit the number of free signed transactions, e.g. `do_something`, by `RateLimits`
+/// of the sender roles. Excess transactions are rejected from the pool, which keeps
+/// a single pending free transaction of the account at a time.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckRateLimit<T: Trait + Send + Sync>(PhantomData<T>);
+
+impl<T: Trait + Send + Sync> CheckRateLimit<T> {
+    /// Create new `CheckRateLimit`.
+    pub fn new() -> Self {
+        CheckRateLimit(PhantomData)
+    }
+}
+
+impl<T: Trait + Send + Sync> Default for CheckRateLimit<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Trait + Send + Sync> fmt::Debug for CheckRateLimit<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CheckRateLimit")
+    }
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for CheckRateLimit<T>
+where
+    <T as frame_system::Trait>::Call: Dispatchable<Info = DispatchInfo>,
+{
+    const IDENTIFIER: &'static str = "CheckRateLimit";
+    type AccountId = T::AccountId;
+    type Call = <T as frame_system::Trait>::Call;
+    type AdditionalSigned = ();
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        _call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> TransactionValidity {
+        if info.pays_fee == Pays::No {
+            if let Some((start, calls)) = Module::<T>::rate_limit_call(who)? {
+                // Pending free transactions of the account take the same call of the window,
+                // so the pool can't collect more of them than the limit allows
+                return ValidTransaction::with_tag_prefix("CheckRateLimit")
+                    .and_provides((who, start, calls))
+                    .build();
+            }
+        }
+        Ok(ValidTransaction::default())
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        _call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> Result<(), TransactionValidityError> {
+        if info.pays_fee == Pays::No {
+            if let Some(usage) = Module::<T>::rate_limit_call(who)? {
+                RateLimitUsage::<T>::insert(who, usage);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<T: Trait> OnKilledAccount<T::AccountId> for Module<T> {
+    /// Apply `ReapPolicy` to the registry entry of the reaped account.
     fn on_killed_account(who: &T::AccountId) {
-        AccountRegistry::<T>::remove(&who);
+        AccountLocks::<T>::remove_prefix(who);
+        RateLimitUsage::<T>::remove(who);
+        if !AccountRegistry::<T>::contains_key(who) {
+            return;
+        }
+        let is_admin = Self::account_is_admin(who);
+        let keep = match T::ReapPolicy::get() {
+            ReapPolicy::Remove => false,
+            ReapPolicy::Keep => true,
+            ReapPolicy::KeepAdmins => is_admin,
+        };
+        // The registry can't be left without admins
+        let keep = keep || (is_admin && Self::admins().len() <= 1);
+        if !keep {
+            Self::remove_account(who);
+        }
+        Self::deposit_event(RawEvent::AccountReaped(who.clone(), keep));
+    }
+}
+
+/// Ensure that origin is signed by an account with ADMIN role in the registry.
+/// Allows other pallets to delegate permissions to the registry admins.
+pub struct EnsureAdmin<T>(PhantomData<T>);
+
+impl<T: Trait> EnsureOrigin<T::Origin> for EnsureAdmin<T> {
+    type Success = T::AccountId;
+
+    fn try_origin(o: T::Origin) -> Result<Self::Success, T::Origin> {
+        o.into().and_then(|o| match o {
+            RawOrigin::Signed(who) if Module::<T>::account_is_admin(&who) => Ok(who),
+            r => Err(T::Origin::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> T::Origin {
+        let admin = AccountRegistry::<T>::iter()
+            .find(|(_, acc)| acc.is_admin())
+            .map(|(who, _)| who)
+            .expect("registry has at least one admin; qed");
+        RawOrigin::Signed(admin).into()
     }
 }
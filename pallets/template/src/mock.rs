@@ -1,15 +1,18 @@
 use crate::{Module, Trait};
 use frame_support::{
-    impl_outer_event, impl_outer_origin, parameter_types,
+    impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types,
+    traits::{ChangeMembers, Get},
     weights::{constants::RocksDbWeight, Weight},
 };
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
-    testing::Header,
+    testing::{Header, TestSignature, TestXt, UintAuthorityId},
     traits::{BlakeTwo256, IdentityLookup},
+    transaction_validity::TransactionPriority,
     Perbill,
 };
+use std::cell::RefCell;
 
 impl_outer_origin! {
     pub enum Origin for Test {}
@@ -29,6 +32,13 @@ impl_outer_event! {
     }
 }
 
+impl_outer_dispatch! {
+    pub enum Call for Test where origin: Origin {
+        template::TemplateModule,
+        balance::Balances,
+    }
+}
+
 // Configure a mock runtime to test the pallet.
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
 pub const SLOT_DURATION: u64 = MILLISECS_PER_BLOCK;
@@ -47,7 +57,7 @@ parameter_types! {
 impl system::Trait for Test {
     type BaseCallFilter = ();
     type Origin = Origin;
-    type Call = ();
+    type Call = Call;
     type Index = u64;
     type BlockNumber = u64;
     type Hash = H256;
@@ -84,9 +94,13 @@ impl pallet_timestamp::Trait for Test {
     type WeightInfo = ();
 }
 
-// Assign module constant values
-parameter_types! {
-    pub const AdminRole: u8 = super::ADMIN_ROLE;
+crate::role_set! {
+    /// Roles of the test runtime, a wide role type leaves room for 64 roles.
+    pub struct TestRoles: u64 {
+        const ADMIN = 0x01;
+        const USER = 0x02;
+        const AUDITOR = 1 << 40;
+    }
 }
 
 struct WeightInfo;
@@ -106,13 +120,181 @@ impl crate::WeightInfo for WeightInfo {
     fn account_add() -> Weight {
         <() as crate::WeightInfo>::account_add()
     }
+    fn report_stale_accounts(n: u32) -> Weight {
+        <() as crate::WeightInfo>::report_stale_accounts(n)
+    }
+    fn account_set_metadata() -> Weight {
+        <() as crate::WeightInfo>::account_set_metadata()
+    }
+    fn account_clear_metadata() -> Weight {
+        <() as crate::WeightInfo>::account_clear_metadata()
+    }
+    fn request_role() -> Weight {
+        <() as crate::WeightInfo>::request_role()
+    }
+    fn approve_request() -> Weight {
+        <() as crate::WeightInfo>::approve_request()
+    }
+    fn reject_request() -> Weight {
+        <() as crate::WeightInfo>::reject_request()
+    }
+    fn force_account_add() -> Weight {
+        <() as crate::WeightInfo>::force_account_add()
+    }
+    fn force_account_disable() -> Weight {
+        <() as crate::WeightInfo>::force_account_disable()
+    }
+    fn force_unlock(l: u32) -> Weight {
+        <() as crate::WeightInfo>::force_unlock(l)
+    }
+    fn set_lock() -> Weight {
+        <() as crate::WeightInfo>::set_lock()
+    }
+    fn remove_lock() -> Weight {
+        <() as crate::WeightInfo>::remove_lock()
+    }
+    fn root_dispatch() -> Weight {
+        <() as crate::WeightInfo>::root_dispatch()
+    }
+    fn account_add_batch(n: u32) -> Weight {
+        <() as crate::WeightInfo>::account_add_batch(n)
+    }
+    fn account_disable_batch(n: u32) -> Weight {
+        <() as crate::WeightInfo>::account_disable_batch(n)
+    }
+    fn add_delegate() -> Weight {
+        <() as crate::WeightInfo>::add_delegate()
+    }
+    fn remove_delegate() -> Weight {
+        <() as crate::WeightInfo>::remove_delegate()
+    }
+    fn delegated_account_add() -> Weight {
+        <() as crate::WeightInfo>::delegated_account_add()
+    }
+    fn delegated_account_disable() -> Weight {
+        <() as crate::WeightInfo>::delegated_account_disable()
+    }
+    fn create_escrow() -> Weight {
+        <() as crate::WeightInfo>::create_escrow()
+    }
+    fn release_escrow() -> Weight {
+        <() as crate::WeightInfo>::release_escrow()
+    }
+    fn cancel_escrow() -> Weight {
+        <() as crate::WeightInfo>::cancel_escrow()
+    }
+    fn set_config() -> Weight {
+        <() as crate::WeightInfo>::set_config()
+    }
+    fn set_config_writers() -> Weight {
+        <() as crate::WeightInfo>::set_config_writers()
+    }
+    fn set_rate_limit() -> Weight {
+        <() as crate::WeightInfo>::set_rate_limit()
+    }
+}
+
+pub type Extrinsic = TestXt<crate::Call<Test>, ()>;
+
+impl system::offchain::SigningTypes for Test {
+    type Public = UintAuthorityId;
+    type Signature = TestSignature;
+}
+
+impl<LocalCall> system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+    crate::Call<Test>: From<LocalCall>,
+{
+    type OverarchingCall = crate::Call<Test>;
+    type Extrinsic = Extrinsic;
+}
+
+thread_local! {
+    /// Admins set passed to `AdminsChanged` handler.
+    pub static ADMINS: RefCell<Vec<u64>> = RefCell::new(vec![]);
+}
+
+thread_local! {
+    /// Policy returned by `TestReapPolicy`.
+    pub static REAP_POLICY: RefCell<crate::ReapPolicy> = RefCell::new(Default::default());
+}
+
+thread_local! {
+    /// Registry entry deposits returned by `TestAdminDeposit` and `TestUserDeposit`.
+    pub static ROLE_DEPOSITS: RefCell<(Balance, Balance)> = RefCell::new((0, 0));
+}
+
+pub struct TestAdminDeposit;
+impl Get<Balance> for TestAdminDeposit {
+    fn get() -> Balance {
+        ROLE_DEPOSITS.with(|deposits| deposits.borrow().0)
+    }
+}
+
+pub struct TestUserDeposit;
+impl Get<Balance> for TestUserDeposit {
+    fn get() -> Balance {
+        ROLE_DEPOSITS.with(|deposits| deposits.borrow().1)
+    }
+}
+
+pub struct TestReapPolicy;
+impl Get<crate::ReapPolicy> for TestReapPolicy {
+    fn get() -> crate::ReapPolicy {
+        REAP_POLICY.with(|policy| *policy.borrow())
+    }
+}
+
+pub struct TestAdminsChanged;
+impl ChangeMembers<u64> for TestAdminsChanged {
+    fn change_members_sorted(_incoming: &[u64], _outgoing: &[u64], new: &[u64]) {
+        ADMINS.with(|admins| *admins.borrow_mut() = new.to_vec());
+    }
+}
+
+/// Offchain worker keys are `UintAuthorityId` equal to the account id.
+pub struct TestAuthId;
+impl system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+    type RuntimeAppPublic = UintAuthorityId;
+    type GenericPublic = UintAuthorityId;
+    type GenericSignature = TestSignature;
+}
+
+parameter_types! {
+    pub const StaleAccountAge: u64 = 100 * MILLISECS_PER_BLOCK;
+    pub const ReportInterval: u64 = 10;
+    pub const UnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+    pub const MaxMetadataLen: u32 = 16;
+    pub const MetadataDepositBase: Balance = 1000;
+    pub const MetadataDepositPerByte: Balance = 10;
+    pub const RoleRequestDeposit: Balance = 500;
+    pub const MaxBatchSize: u32 = 3;
+    pub const MaxConfigLen: u32 = 32;
+    pub const MaxConfigHistory: u32 = 2;
 }
 
 impl Trait for Test {
     type Event = TestEvent;
-    type AdminRole = AdminRole;
-    type AccountRole = u8;
+    type AccountRole = TestRoles;
     type Currency = pallet_balances::Module<Self>;
+    type MaxMetadataLen = MaxMetadataLen;
+    type MetadataDepositBase = MetadataDepositBase;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type RoleRequestDeposit = RoleRequestDeposit;
+    type AdminDeposit = TestAdminDeposit;
+    type UserDeposit = TestUserDeposit;
+    type MaxLocks = MaxLocks;
+    type MaxConfigLen = MaxConfigLen;
+    type MaxConfigHistory = MaxConfigHistory;
+    type AdminOrigin = system::EnsureRoot<u64>;
+    type AdminsChanged = TestAdminsChanged;
+    type MaxBatchSize = MaxBatchSize;
+    type ReapPolicy = TestReapPolicy;
+    type Call = Call;
+    type AuthorityId = TestAuthId;
+    type StaleAccountAge = StaleAccountAge;
+    type ReportInterval = ReportInterval;
+    type UnsignedPriority = UnsignedPriority;
     type WeightInfo = ();
 }
 
@@ -131,7 +313,7 @@ impl pallet_balances::Trait for Test {
     type MaxLocks = MaxLocks;
 }
 
-// pub type Balances = pallet_balances::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
 // parameter_types! {
 //     pub const TransactionByteFee: Balance = 1;
 // }
@@ -150,12 +332,15 @@ pub type Account = super::AccountOf<Test>;
 static INITIAL: [(
     <Test as system::Trait>::AccountId,
     <Test as super::Trait>::AccountRole,
-); 1] = [(1, super::ADMIN_ROLE)];
+); 1] = [(1, TestRoles::ADMIN)];
 
-static INITIAL_BALANCE: super::BalanceOf<Test> = 100000;
+pub static INITIAL_BALANCE: super::BalanceOf<Test> = 100000;
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
+    // Thread-local configuration outlives the test, start from the defaults
+    REAP_POLICY.with(|policy| *policy.borrow_mut() = Default::default());
+    ROLE_DEPOSITS.with(|deposits| *deposits.borrow_mut() = (0, 0));
     let mut storage = system::GenesisConfig::default()
         .build_storage::<Test>()
         .unwrap();
@@ -181,6 +366,7 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
                 )
             })
             .collect(),
+        rate_limits: vec![],
     }
     .assimilate_storage(&mut storage)
     .unwrap();
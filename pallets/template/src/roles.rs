@@ -0,0 +1,172 @@
+//! Typed sets of account roles.
+//!
+//! Runtimes declare their roles with `role_set!` over an unsigned integer of the required
+//! width, e.g. `u8` for up to 8 roles or `u64` for up to 64 roles.
+
+use frame_support::{dispatch::Parameter, sp_runtime::traits::MaybeSerializeDeserialize};
+
+/// Set of account roles kept in the registry.
+pub trait RoleSet: Parameter + MaybeSerializeDeserialize + Default + Copy {
+    /// Role of the registry admins.
+    const ADMIN: Self;
+    /// All the roles known to the runtime.
+    const ALL: Self;
+
+    /// Roles present in both sets.
+    fn intersection(self, other: Self) -> Self;
+
+    /// Set has no roles.
+    fn is_empty(&self) -> bool;
+
+    /// Set has all the roles of `other`.
+    fn contains(self, other: Self) -> bool {
+        self.intersection(other) == other
+    }
+
+    /// Set has no roles unknown to the runtime.
+    fn is_known(self) -> bool {
+        Self::ALL.contains(self)
+    }
+}
+
+/// Declare a `bitflags`-style set of account roles implementing `RoleSet`.
+/// `ADMIN` role is required.
+///
+/// ```ignore
+/// pallet_template::role_set! {
+///     /// Roles of the registry accounts.
+///     pub struct AccountRoles: u16 {
+///         /// Registry admin.
+///         const ADMIN = 0x0001;
+///         /// Regular user.
+///         const USER = 0x0002;
+///     }
+/// }
+///
+/// let roles = AccountRoles::ADMIN | AccountRoles::USER;
+/// ```
+#[macro_export]
+macro_rules! role_set {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident: $bits:ty {
+            $(
+                $(#[$role_attr:meta])*
+                const $role:ident = $value:expr;
+            )+
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        $vis struct $name($bits);
+
+        #[allow(dead_code)]
+        impl $name {
+            $(
+                $(#[$role_attr])*
+                pub const $role: Self = $name($value);
+            )+
+
+            /// Set without roles.
+            pub const fn empty() -> Self {
+                $name(0)
+            }
+
+            /// Raw value of the set.
+            pub const fn bits(&self) -> $bits {
+                self.0
+            }
+
+            /// Set from the raw value, `None` if it has unknown roles.
+            pub fn from_bits(bits: $bits) -> Option<Self> {
+                let roles = $name(bits);
+                if <Self as $crate::RoleSet>::is_known(roles) {
+                    Some(roles)
+                } else {
+                    None
+                }
+            }
+
+            /// Set from the raw value keeping unknown roles.
+            pub const fn from_bits_unchecked(bits: $bits) -> Self {
+                $name(bits)
+            }
+        }
+
+        impl $crate::RoleSet for $name {
+            const ADMIN: Self = $name::ADMIN;
+            const ALL: Self = $name(0 $(| $value)+);
+
+            fn intersection(self, other: Self) -> Self {
+                $name(self.0 & other.0)
+            }
+
+            fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+        }
+
+        impl core::ops::BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, other: Self) -> Self {
+                $name(self.0 | other.0)
+            }
+        }
+
+        impl core::ops::BitAnd for $name {
+            type Output = Self;
+
+            fn bitand(self, other: Self) -> Self {
+                $name(self.0 & other.0)
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.debug_tuple(stringify!($name)).field(&self.0).finish()
+            }
+        }
+
+        // Encoded as the raw value, so it replaces an integer role type of the same width
+        // without migration. A wider type changes the encoding and needs a storage migration.
+        impl $crate::codec::Encode for $name {
+            fn size_hint(&self) -> usize {
+                $crate::codec::Encode::size_hint(&self.0)
+            }
+
+            fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+                $crate::codec::Encode::using_encoded(&self.0, f)
+            }
+        }
+
+        impl $crate::codec::EncodeLike for $name {}
+
+        impl $crate::codec::Decode for $name {
+            fn decode<I: $crate::codec::Input>(
+                input: &mut I,
+            ) -> core::result::Result<Self, $crate::codec::Error> {
+                <$bits as $crate::codec::Decode>::decode(input).map($name)
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl $crate::serde::Serialize for $name {
+            fn serialize<S: $crate::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> core::result::Result<S::Ok, S::Error> {
+                $crate::serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl<'de> $crate::serde::Deserialize<'de> for $name {
+            fn deserialize<D: $crate::serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> core::result::Result<Self, D::Error> {
+                <$bits as $crate::serde::Deserialize<'de>>::deserialize(deserializer).map($name)
+            }
+        }
+    };
+}
@@ -2,9 +2,10 @@ use crate::mock::*;
 use frame_support::{
     assert_noop, assert_ok,
     dispatch::{DispatchInfo, DispatchResultWithPostInfo, GetDispatchInfo},
-    traits::UnfilteredDispatchable,
+    traits::{Get, LockIdentifier, UnfilteredDispatchable},
     weights::{Pays, Weight},
 };
+use sp_runtime::testing::{TestSignature, UintAuthorityId};
 
 // Learn more about testing substrate runtime modules
 // https://substrate.dev/docs/en/knowledgebase/runtime/tests
@@ -23,6 +24,15 @@ fn events() -> Vec<TestEvent> {
     System::reset_events();
     evt
 }
+
+// get template pallet events only and reset
+fn template_events() -> Vec<TestEvent> {
+    events()
+        .into_iter()
+        .filter(|evt| matches!(evt, TestEvent::template(_)))
+        .collect()
+}
+
 #[allow(dead_code)]
 fn last_event() -> TestEvent {
     System::events().pop().expect("Event expected").event
@@ -72,7 +82,10 @@ fn it_works_for_default_value() {
         assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
         assert_eq!(
             events(),
-            vec![TestEvent::template(super::RawEvent::SomethingStored(42, 1))]
+            vec![
+                TestEvent::template(super::RawEvent::ConfigUpdated(1, b"something".to_vec(), 1)),
+                TestEvent::template(super::RawEvent::SomethingStored(42, 1)),
+            ]
         );
 
         // Read pallet storage and assert an expected result.
@@ -92,7 +105,7 @@ fn it_create_new_account() {
         assert_ok!(TemplateModule::account_add(
             Origin::signed(1),
             2,
-            super::USER_ROLE
+            TestRoles::USER
         ));
 
         let account = TemplateModule::account_registry(2);
@@ -101,9 +114,26 @@ fn it_create_new_account() {
         let age = account.age(20000);
         assert_eq!(age, 15000);
 
-        assert_ok!(TemplateModule::do_something(Origin::signed(2), 20));
+        // Only the admins update the value until users are allowed to
+        assert_noop!(
+            TemplateModule::update_something(Origin::signed(2), 20),
+            Error::NotAuthorized
+        );
+        assert_ok!(TemplateModule::set_config_writers(
+            Origin::root(),
+            super::SOMETHING_KEY.to_vec(),
+            TestRoles::USER
+        ));
+        assert_ok!(TemplateModule::update_something(Origin::signed(2), 20));
         System::set_block_number(2);
         assert_eq!(TemplateModule::something(), Some(20));
+        assert_noop!(
+            TemplateModule::update_something(Origin::signed(3), 30),
+            Error::NotAuthorized
+        );
+        // The free call is open to any signed origin
+        assert_ok!(TemplateModule::do_something(Origin::signed(3), 30));
+        assert_eq!(TemplateModule::something(), Some(30));
     });
 }
 
@@ -113,7 +143,7 @@ fn it_disable_account() {
         assert_ok!(TemplateModule::account_add(
             Origin::signed(1),
             2,
-            super::USER_ROLE
+            TestRoles::USER
         ));
         assert_ok!(TemplateModule::account_disable(Origin::signed(1), 2));
         assert!(!TemplateModule::account_registry(2).is_enable());
@@ -125,27 +155,154 @@ fn it_try_disable_themself() {
     new_test_ext().execute_with(|| {
         assert_noop!(
             TemplateModule::account_disable(Origin::signed(1), 1),
-            Error::InvalidAction
+            Error::CannotDisableSelf
         );
         assert!(TemplateModule::account_registry(1).is_enable());
     });
 }
 
+#[test]
+fn it_try_drop_own_admin_role() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            TemplateModule::account_add(Origin::signed(1), 1, TestRoles::USER),
+            Error::CannotDisableSelf
+        );
+        assert_noop!(
+            TemplateModule::account_add(Origin::signed(1), 1, TestRoles::empty()),
+            Error::CannotDisableSelf
+        );
+        assert_noop!(
+            TemplateModule::account_add_batch(
+                Origin::signed(1),
+                vec![(2, TestRoles::ADMIN), (1, TestRoles::USER)]
+            ),
+            Error::CannotDisableSelf
+        );
+        assert!(TemplateModule::account_is_admin(&1));
+        assert_eq!(TemplateModule::admins(), vec![1]);
+    });
+}
+
 #[test]
 fn it_try_create_by_user() {
     new_test_ext().execute_with(|| {
         assert_ok!(TemplateModule::account_add(
             Origin::signed(1),
             2,
-            super::USER_ROLE
+            TestRoles::USER
         ));
         assert_noop!(
-            TemplateModule::account_add(Origin::signed(2), 3, super::USER_ROLE),
+            TemplateModule::account_add(Origin::signed(2), 3, TestRoles::USER),
             Error::NotAuthorized
         );
     });
 }
 
+#[test]
+fn it_try_create_with_unknown_role() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            TemplateModule::account_add(Origin::signed(1), 2, TestRoles::from_bits_unchecked(0x04)),
+            Error::UnknownRole
+        );
+        assert_noop!(
+            TemplateModule::account_add_batch(
+                Origin::signed(1),
+                vec![
+                    (2, TestRoles::USER),
+                    (3, TestRoles::USER | TestRoles::from_bits_unchecked(0x80))
+                ]
+            ),
+            Error::UnknownRole
+        );
+        assert_noop!(
+            TemplateModule::force_account_add(
+                Origin::root(),
+                2,
+                TestRoles::from_bits_unchecked(0x04)
+            ),
+            Error::UnknownRole
+        );
+        assert_noop!(
+            TemplateModule::request_role(Origin::signed(2), TestRoles::empty(), vec![]),
+            Error::UnknownRole
+        );
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::ADMIN | TestRoles::USER
+        ));
+    });
+}
+
+#[test]
+fn it_use_wide_roles() {
+    use super::RoleSet;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::AUDITOR | TestRoles::USER
+        ));
+        let roles = TemplateModule::account_registry(2).roles;
+        assert!(roles.contains(TestRoles::AUDITOR));
+        assert!(!TemplateModule::account_registry(2).is_admin());
+        assert_eq!(roles.bits(), (1 << 40) | 0x02);
+
+        assert_eq!(TestRoles::ALL.bits(), (1 << 40) | 0x03);
+        assert_eq!(TestRoles::from_bits(1 << 40), Some(TestRoles::AUDITOR));
+        assert_eq!(TestRoles::from_bits(1 << 41), None);
+        assert_noop!(
+            TemplateModule::account_add(
+                Origin::signed(1),
+                3,
+                TestRoles::from_bits_unchecked(1 << 41)
+            ),
+            Error::UnknownRole
+        );
+    });
+}
+
+#[test]
+fn it_try_grant_admin_to_admin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            TemplateModule::account_add(Origin::signed(1), 1, TestRoles::ADMIN),
+            Error::AlreadyAdmin
+        );
+        assert_noop!(
+            TemplateModule::account_add_batch(Origin::signed(1), vec![(1, TestRoles::ADMIN)]),
+            Error::AlreadyAdmin
+        );
+        assert_noop!(
+            TemplateModule::request_role(Origin::signed(1), TestRoles::ADMIN, vec![]),
+            Error::AlreadyAdmin
+        );
+        // Admin can update their own roles keeping ADMIN
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            1,
+            TestRoles::ADMIN | TestRoles::USER
+        ));
+        assert_eq!(
+            TemplateModule::account_registry(1).roles,
+            TestRoles::ADMIN | TestRoles::USER
+        );
+        assert_noop!(
+            TemplateModule::account_add(Origin::signed(1), 1, TestRoles::ADMIN | TestRoles::USER),
+            Error::AlreadyAdmin
+        );
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            1,
+            TestRoles::ADMIN
+        ));
+        assert_eq!(TemplateModule::admins(), vec![1]);
+    });
+}
+
 #[test]
 fn it_account_reaped() {
     new_test_ext().execute_with(|| {
@@ -153,7 +310,7 @@ fn it_account_reaped() {
         assert_ok!(TemplateModule::account_add(
             Origin::signed(1),
             2,
-            super::USER_ROLE
+            TestRoles::USER
         ));
 
         assert!(TemplateModule::account_registry(2).is_enable());
@@ -162,6 +319,16 @@ fn it_account_reaped() {
     });
 }
 
+#[test]
+fn it_try_lock_more_than_free_balance() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            TemplateModule::account_transfer_and_lock(Origin::signed(1), 2, 100001),
+            Error::InsufficientBalanceForLock
+        );
+    });
+}
+
 #[test]
 fn it_lock_balance() {
     new_test_ext().execute_with(|| {
@@ -173,11 +340,19 @@ fn it_lock_balance() {
         assert_ok!(TemplateModule::account_add(
             Origin::signed(1),
             2,
-            super::USER_ROLE
+            TestRoles::USER
         ));
 
         assert!(TemplateModule::account_registry(2).is_enable());
         assert_eq!(Balances::free_balance(2), 10000);
+        assert_eq!(
+            TemplateModule::account_lock(2, super::FEE_LOCK_ID),
+            Some(super::LockInfo {
+                amount: 10000,
+                reasons: super::LockReasons::ExceptFee,
+                creator: 1,
+            })
+        );
         assert_noop!(
             Balances::transfer(Origin::signed(2), 3, 5000),
             pallet_balances::Error::<Test, _>::LiquidityRestrictions
@@ -186,104 +361,1764 @@ fn it_lock_balance() {
 }
 
 #[test]
-fn it_balance() {
+fn it_set_named_locks() {
+    const GRANT: LockIdentifier = *b"grant   ";
+    const PENALTY: LockIdentifier = *b"penalty ";
+
     new_test_ext().execute_with(|| {
-        //total_issuance
-        assert_eq!(Balances::total_issuance(), 100000);
-        assert_eq!(Balances::free_balance(1), 100000);
-        assert_eq!(Balances::free_balance(2), 0);
-        assert_ok!(TemplateModule::account_add(
+        System::set_block_number(1);
+        assert_ok!(Balances::transfer(Origin::signed(1), 2, 10000));
+        assert_noop!(
+            TemplateModule::set_lock(Origin::signed(2), 2, GRANT, 1000, super::LockReasons::All),
+            Error::NotAuthorized
+        );
+        assert_noop!(
+            TemplateModule::set_lock(Origin::signed(1), 2, GRANT, 10001, super::LockReasons::All),
+            Error::InsufficientBalanceForLock
+        );
+        events();
+
+        assert_ok!(TemplateModule::set_lock(
             Origin::signed(1),
-            3,
-            super::USER_ROLE
+            2,
+            GRANT,
+            3000,
+            super::LockReasons::All
+        ));
+        assert_ok!(TemplateModule::set_lock(
+            Origin::signed(1),
+            2,
+            PENALTY,
+            5000,
+            super::LockReasons::ExceptFee
         ));
+        // Locks overlay, the largest one applies
+        assert_noop!(
+            Balances::transfer(Origin::signed(2), 3, 5001),
+            pallet_balances::Error::<Test, _>::LiquidityRestrictions
+        );
+        let mut locks = TemplateModule::account_locks(&2);
+        locks.sort_by_key(|(id, _)| *id);
+        assert_eq!(
+            locks,
+            vec![
+                (
+                    GRANT,
+                    super::LockInfo {
+                        amount: 3000,
+                        reasons: super::LockReasons::All,
+                        creator: 1,
+                    }
+                ),
+                (
+                    PENALTY,
+                    super::LockInfo {
+                        amount: 5000,
+                        reasons: super::LockReasons::ExceptFee,
+                        creator: 1,
+                    }
+                ),
+            ]
+        );
 
-        assert_ok!(Balances::transfer(Origin::signed(1), 2, 50000));
+        // Locks are removed individually
+        assert_ok!(TemplateModule::remove_lock(Origin::signed(1), 2, PENALTY));
+        assert_noop!(
+            TemplateModule::remove_lock(Origin::signed(1), 2, PENALTY),
+            Error::LockNotFound
+        );
+        assert_ok!(Balances::transfer(Origin::signed(2), 3, 5000));
+        assert_eq!(TemplateModule::account_locks(&2).len(), 1);
+        assert_eq!(
+            template_events(),
+            vec![
+                TestEvent::template(super::RawEvent::LockSet(1, 2, GRANT, 3000)),
+                TestEvent::template(super::RawEvent::LockSet(1, 2, PENALTY, 5000)),
+                TestEvent::template(super::RawEvent::LockRemoved(1, 2, PENALTY)),
+            ]
+        );
 
-        assert_eq!(Balances::free_balance(1), 50000);
-        assert_eq!(Balances::free_balance(2), 50000);
-        assert_eq!(Balances::total_issuance(), 100000);
-        assert_ok!(Balances::transfer(Origin::signed(1), 2, 49990));
-        // Account 1 has been removed from balances and dust remaining 10
-        assert_eq!(Balances::total_issuance(), 99990);
+        // Zero lock releases the balance
+        assert_ok!(TemplateModule::set_lock(
+            Origin::signed(1),
+            2,
+            GRANT,
+            0,
+            super::LockReasons::All
+        ));
+        assert!(Balances::locks(2).is_empty());
+        assert_ok!(Balances::transfer(Origin::signed(2), 3, 4000));
     });
 }
 
 #[test]
-fn it_dispatchable_weight() {
-    // pre-dispatch weights
-    fn assert_dispatch(call: crate::Call<Test>, weight: Weight, pay: Pays) -> DispatchInfo {
-        let dispatch_info = call.get_dispatch_info();
-
-        assert_eq!(dispatch_info.weight, weight);
-        assert_eq!(dispatch_info.pays_fee, pay);
-        dispatch_info
-    }
-    let call = crate::Call::<Test>::account_add(3, 1);
-    assert_dispatch(call, <() as super::WeightInfo>::account_add(), Pays::Yes);
+fn it_try_exceed_max_locks() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            TemplateModule::set_lock(
+                Origin::signed(1),
+                2,
+                *b"no funds",
+                100,
+                super::LockReasons::All
+            ),
+            Error::NoBalance
+        );
+        assert_ok!(Balances::transfer(Origin::signed(1), 2, 10000));
+        for i in 0..MaxLocks::get() {
+            let id = (i as u64).to_le_bytes();
+            assert_ok!(TemplateModule::set_lock(
+                Origin::signed(1),
+                2,
+                id,
+                100,
+                super::LockReasons::All
+            ));
+        }
+        assert_noop!(
+            TemplateModule::set_lock(
+                Origin::signed(1),
+                2,
+                *b"one more",
+                100,
+                super::LockReasons::All
+            ),
+            Error::TooManyLocks
+        );
+        // Existing lock can be updated
+        assert_ok!(TemplateModule::set_lock(
+            Origin::signed(1),
+            2,
+            0u64.to_le_bytes(),
+            200,
+            super::LockReasons::ExceptFee
+        ));
+    });
+}
 
-    let call = crate::Call::<Test>::update_something(3);
-    assert_dispatch(
-        call,
-        <() as super::WeightInfo>::update_something(),
-        Pays::Yes,
-    );
+#[test]
+fn it_release_escrow_after_block() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+        events();
 
-    // post-dispatch weights
-    fn assert_call(
-        origin: Origin,
-        call: crate::Call<Test>,
-        weight: Weight,
-        pay: Pays,
-    ) -> DispatchResultWithPostInfo {
-        let dispatch_info = call.get_dispatch_info();
-        let dispatch_post_info = call.dispatch_bypass_filter(origin)?;
+        assert_ok!(TemplateModule::create_escrow(
+            Origin::signed(1),
+            2,
+            5000,
+            super::ReleaseCondition::After(10)
+        ));
+        assert_eq!(Balances::reserved_balance(1), 5000);
+        assert_eq!(TemplateModule::next_escrow_id(), 1);
+        assert_eq!(
+            TemplateModule::escrows(0),
+            Some(super::Escrow {
+                creator: 1,
+                counterparty: 2,
+                amount: 5000,
+                condition: super::ReleaseCondition::After(10),
+                approvals: vec![],
+            })
+        );
+        assert_noop!(
+            TemplateModule::release_escrow(Origin::signed(2), 0),
+            Error::EscrowLocked
+        );
 
-        assert_eq!(dispatch_post_info.pays_fee(&dispatch_info), pay);
+        // Anyone can release the escrow in time
+        System::set_block_number(10);
+        assert_ok!(TemplateModule::release_escrow(Origin::signed(3), 0));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 95000);
+        assert_eq!(Balances::free_balance(2), 5000);
+        assert_eq!(TemplateModule::escrows(0), None);
         assert_eq!(
-            dispatch_post_info.calc_actual_weight(&dispatch_info),
-            weight
+            template_events(),
+            vec![
+                TestEvent::template(super::RawEvent::EscrowCreated(0, 1, 2, 5000)),
+                TestEvent::template(super::RawEvent::EscrowReleased(0, 2, 5000)),
+            ]
         );
-        Ok(dispatch_post_info)
-    }
+        assert_noop!(
+            TemplateModule::release_escrow(Origin::signed(2), 0),
+            Error::NoEscrow
+        );
+    });
+}
 
+#[test]
+fn it_release_escrow_by_approvals() {
     new_test_ext().execute_with(|| {
-        let call = crate::Call::<Test>::update_something(300);
-        assert_ok!(assert_call(
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::account_add_batch(
             Origin::signed(1),
-            call,
-            <() as super::WeightInfo>::update_something(),
-            Pays::Yes
+            vec![(2, TestRoles::USER), (3, TestRoles::USER)]
         ));
+        assert_ok!(Balances::transfer(Origin::signed(1), 2, 20000));
 
-        let call = crate::Call::<Test>::update_something(100);
-        assert_ok!(assert_call(
-            Origin::signed(1),
-            call,
-            <() as super::WeightInfo>::update_something(),
-            Pays::No
+        assert_ok!(TemplateModule::create_escrow(
+            Origin::signed(2),
+            3,
+            1000,
+            super::ReleaseCondition::AdminApproval
+        ));
+        assert_noop!(
+            TemplateModule::release_escrow(Origin::signed(2), 0),
+            Error::NotAuthorized
+        );
+        assert_ok!(TemplateModule::release_escrow(Origin::signed(1), 0));
+        assert_eq!(Balances::free_balance(3), 1000);
+
+        assert_ok!(TemplateModule::create_escrow(
+            Origin::signed(2),
+            3,
+            1000,
+            super::ReleaseCondition::BothParties
         ));
+        events();
+        assert_noop!(
+            TemplateModule::release_escrow(Origin::signed(1), 1),
+            Error::NotAuthorized
+        );
+        assert_ok!(TemplateModule::release_escrow(Origin::signed(3), 1));
+        assert_ok!(TemplateModule::release_escrow(Origin::signed(3), 1));
+        assert_eq!(TemplateModule::escrows(1).unwrap().approvals, vec![3]);
+        assert_eq!(Balances::reserved_balance(2), 1000);
 
-        let call = crate::Call::<Test>::account_add(3, 1);
-        assert_ok!(assert_call(
+        assert_ok!(TemplateModule::release_escrow(Origin::signed(2), 1));
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::free_balance(2), 18000);
+        assert_eq!(Balances::free_balance(3), 2000);
+        assert_eq!(
+            template_events(),
+            vec![
+                TestEvent::template(super::RawEvent::EscrowApproved(1, 3)),
+                TestEvent::template(super::RawEvent::EscrowApproved(1, 2)),
+                TestEvent::template(super::RawEvent::EscrowReleased(1, 3, 1000)),
+            ]
+        );
+    });
+}
+
+#[test]
+fn it_cancel_escrow() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::account_add_batch(
             Origin::signed(1),
-            call,
-            <() as super::WeightInfo>::account_add(),
-            Pays::Yes
+            vec![(2, TestRoles::USER), (3, TestRoles::USER)]
         ));
+        assert_ok!(Balances::transfer(Origin::signed(1), 2, 20000));
 
-        let call = crate::Call::<Test>::account_disable(3);
-        assert_ok!(assert_call(
+        let condition = super::ReleaseCondition::BothParties;
+        assert_noop!(
+            TemplateModule::create_escrow(Origin::signed(4), 2, 1000, condition),
+            Error::NotRegistered
+        );
+        assert_noop!(
+            TemplateModule::create_escrow(Origin::signed(2), 4, 1000, condition),
+            Error::NotRegistered
+        );
+        assert_noop!(
+            TemplateModule::create_escrow(Origin::signed(2), 2, 1000, condition),
+            Error::InvalidData
+        );
+        assert_noop!(
+            TemplateModule::create_escrow(Origin::signed(2), 3, 0, condition),
+            Error::InvalidData
+        );
+        assert_noop!(
+            TemplateModule::create_escrow(
+                Origin::signed(2),
+                3,
+                ExistentialDeposit::get() as Balance - 1,
+                condition
+            ),
+            Error::InvalidData
+        );
+        assert_noop!(
+            TemplateModule::create_escrow(Origin::signed(2), 3, 30000, condition),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+
+        assert_ok!(TemplateModule::create_escrow(
+            Origin::signed(2),
+            3,
+            1000,
+            condition
+        ));
+        events();
+        // The creator can't take the funds back alone
+        assert_noop!(
+            TemplateModule::cancel_escrow(Origin::signed(2), 0),
+            Error::NotAuthorized
+        );
+        assert_ok!(TemplateModule::cancel_escrow(Origin::signed(3), 0));
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::free_balance(2), 20000);
+        assert_eq!(TemplateModule::escrows(0), None);
+        assert_eq!(
+            template_events(),
+            vec![TestEvent::template(super::RawEvent::EscrowCancelled(0, 3))]
+        );
+
+        // Admins cancel any escrow
+        assert_ok!(TemplateModule::create_escrow(
+            Origin::signed(2),
+            3,
+            1000,
+            condition
+        ));
+        assert_ok!(TemplateModule::cancel_escrow(Origin::signed(1), 1));
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+#[test]
+fn it_set_config_by_writers() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::account_add(
             Origin::signed(1),
-            call,
-            <() as super::WeightInfo>::account_disable(),
-            Pays::Yes
+            2,
+            TestRoles::USER | TestRoles::AUDITOR
         ));
+        events();
 
-        let call = crate::Call::<Test>::update_something(300);
+        // Admins only by default
         assert_noop!(
-            assert_call(Origin::signed(2), call, 0, Pays::Yes),
+            TemplateModule::set_config(Origin::signed(2), b"fee".to_vec(), Some(vec![1])),
             Error::NotAuthorized
         );
+        assert_ok!(TemplateModule::set_config(
+            Origin::signed(1),
+            b"fee".to_vec(),
+            Some(vec![1])
+        ));
+        assert_eq!(TemplateModule::config_value(b"fee".to_vec()), Some(vec![1]));
+        assert_eq!(
+            template_events(),
+            vec![TestEvent::template(super::RawEvent::ConfigUpdated(
+                1,
+                b"fee".to_vec(),
+                1
+            ))]
+        );
+
+        assert_noop!(
+            TemplateModule::set_config_writers(
+                Origin::signed(1),
+                b"fee".to_vec(),
+                TestRoles::AUDITOR
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            TemplateModule::set_config_writers(
+                Origin::root(),
+                b"fee".to_vec(),
+                TestRoles::from_bits_unchecked(0x80)
+            ),
+            Error::UnknownRole
+        );
+        assert_ok!(TemplateModule::set_config_writers(
+            Origin::root(),
+            b"fee".to_vec(),
+            TestRoles::AUDITOR
+        ));
+        assert_eq!(
+            template_events(),
+            vec![TestEvent::template(super::RawEvent::ConfigWritersSet(
+                b"fee".to_vec(),
+                TestRoles::AUDITOR
+            ))]
+        );
+
+        // Admins aren't writers of the key anymore
+        assert_noop!(
+            TemplateModule::set_config(Origin::signed(1), b"fee".to_vec(), Some(vec![2])),
+            Error::NotAuthorized
+        );
+        assert_ok!(TemplateModule::set_config(
+            Origin::signed(2),
+            b"fee".to_vec(),
+            None
+        ));
+        assert_eq!(TemplateModule::config_value(b"fee".to_vec()), None);
+        assert_eq!(TemplateModule::config_revision(b"fee".to_vec()), 2);
+
+        assert_ok!(TemplateModule::set_config_writers(
+            Origin::root(),
+            b"fee".to_vec(),
+            TestRoles::empty()
+        ));
+        assert_eq!(TemplateModule::config_writers(b"fee".to_vec()), None);
+        assert_ok!(TemplateModule::set_config(
+            Origin::signed(1),
+            b"fee".to_vec(),
+            Some(vec![3])
+        ));
+    });
+}
+
+#[test]
+fn it_try_set_too_long_config() {
+    new_test_ext().execute_with(|| {
+        let max_len = MaxConfigLen::get() as usize;
+        assert_noop!(
+            TemplateModule::set_config(Origin::signed(1), vec![0; max_len + 1], None),
+            Error::ConfigTooLong
+        );
+        assert_noop!(
+            TemplateModule::set_config(
+                Origin::signed(1),
+                b"fee".to_vec(),
+                Some(vec![0; max_len + 1])
+            ),
+            Error::ConfigTooLong
+        );
+        assert_noop!(
+            TemplateModule::set_config_writers(
+                Origin::root(),
+                vec![0; max_len + 1],
+                TestRoles::USER
+            ),
+            Error::ConfigTooLong
+        );
+        assert_ok!(TemplateModule::set_config(
+            Origin::signed(1),
+            vec![0; max_len],
+            Some(vec![0; max_len])
+        ));
+    });
+}
+
+#[test]
+fn it_keep_config_history() {
+    new_test_ext().execute_with(|| {
+        let key = b"fee".to_vec();
+        for (block_number, value) in (1..=3).zip(10u8..) {
+            System::set_block_number(block_number);
+            assert_ok!(TemplateModule::set_config(
+                Origin::signed(1),
+                key.clone(),
+                Some(vec![value])
+            ));
+        }
+
+        // Only the last `MaxConfigHistory` changes are kept
+        assert_eq!(TemplateModule::config_revision(&key), 3);
+        assert_eq!(TemplateModule::config_history(&key, 1), None);
+        assert_eq!(
+            TemplateModule::config_history(&key, 2),
+            Some(super::ConfigChange {
+                value: Some(vec![11]),
+                who: 1,
+                block_number: 2,
+            })
+        );
+        assert_eq!(
+            TemplateModule::config_history(&key, 3),
+            Some(super::ConfigChange {
+                value: Some(vec![12]),
+                who: 1,
+                block_number: 3,
+            })
+        );
+    });
+}
+
+#[test]
+fn it_read_config_parameter() {
+    use codec::Encode;
+    use frame_support::parameter_types;
+
+    parameter_types! {
+        pub const LimitKey: &'static [u8] = b"limit";
+        pub const DefaultLimit: u32 = 5;
+    }
+    type Limit = super::ConfigParameter<LimitKey, u32, DefaultLimit>;
+
+    new_test_ext().execute_with(|| {
+        assert_eq!(Limit::get(), 5);
+
+        assert_ok!(TemplateModule::set_config(
+            Origin::signed(1),
+            b"limit".to_vec(),
+            Some(7u32.encode())
+        ));
+        assert_eq!(Limit::get(), 7);
+
+        // Falls back to the default on the value of another type
+        assert_ok!(TemplateModule::set_config(
+            Origin::signed(1),
+            b"limit".to_vec(),
+            Some(vec![1])
+        ));
+        assert_eq!(Limit::get(), 5);
+    });
+}
+
+#[test]
+fn it_migrate_something_to_config() {
+    use frame_support::{storage::migration, traits::OnRuntimeUpgrade};
+
+    new_test_ext().execute_with(|| {
+        migration::put_storage_value(b"TemplateModule", b"Something", &[], 42u32);
+        assert_eq!(TemplateModule::something(), None);
+
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::something(), Some(42));
+        assert_eq!(
+            migration::get_storage_value::<u32>(b"TemplateModule", b"Something", &[]),
+            None
+        );
+    });
+}
+
+#[test]
+fn it_set_rate_limit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let limit = super::RateLimit {
+            calls: 2,
+            period: 10,
+        };
+        assert_noop!(
+            TemplateModule::set_rate_limit(Origin::signed(1), TestRoles::USER, Some(limit)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            TemplateModule::set_rate_limit(
+                Origin::root(),
+                TestRoles::USER,
+                Some(super::RateLimit {
+                    calls: 2,
+                    period: 0,
+                })
+            ),
+            Error::InvalidData
+        );
+        assert_noop!(
+            TemplateModule::set_rate_limit(
+                Origin::root(),
+                TestRoles::from_bits_unchecked(0x80),
+                Some(limit)
+            ),
+            Error::UnknownRole
+        );
+
+        assert_ok!(TemplateModule::set_rate_limit(
+            Origin::root(),
+            TestRoles::USER,
+            Some(limit)
+        ));
+        assert_eq!(TemplateModule::rate_limits(TestRoles::USER), Some(limit));
+        assert_eq!(
+            events(),
+            vec![TestEvent::template(super::RawEvent::RateLimitSet(
+                TestRoles::USER,
+                Some(limit)
+            ))]
+        );
+
+        assert_ok!(TemplateModule::set_rate_limit(
+            Origin::root(),
+            TestRoles::USER,
+            None
+        ));
+        assert_eq!(TemplateModule::rate_limits(TestRoles::USER), None);
+    });
+}
+
+#[test]
+fn it_apply_widest_rate_limit_of_roles() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER | TestRoles::AUDITOR
+        ));
+        assert_eq!(TemplateModule::account_rate_limit(&2), None);
+
+        let default_limit = super::RateLimit {
+            calls: 1,
+            period: 10,
+        };
+        let user_limit = super::RateLimit {
+            calls: 3,
+            period: 10,
+        };
+        let auditor_limit = super::RateLimit {
+            calls: 5,
+            period: 20,
+        };
+        assert_ok!(TemplateModule::set_rate_limit(
+            Origin::root(),
+            TestRoles::empty(),
+            Some(default_limit)
+        ));
+        assert_ok!(TemplateModule::set_rate_limit(
+            Origin::root(),
+            TestRoles::USER,
+            Some(user_limit)
+        ));
+        assert_ok!(TemplateModule::set_rate_limit(
+            Origin::root(),
+            TestRoles::AUDITOR,
+            Some(auditor_limit)
+        ));
+
+        // Limit of the empty roles applies to unregistered accounts
+        assert_eq!(TemplateModule::account_rate_limit(&3), Some(default_limit));
+        assert_eq!(TemplateModule::account_rate_limit(&1), Some(default_limit));
+        // 3 calls per 10 blocks are wider than 5 calls per 20 blocks
+        assert_eq!(TemplateModule::account_rate_limit(&2), Some(user_limit));
+    });
+}
+
+#[test]
+fn it_reject_free_calls_over_rate_limit() {
+    use codec::Encode;
+    use sp_runtime::{
+        traits::SignedExtension,
+        transaction_validity::{InvalidTransaction, TransactionValidityError},
+    };
+    type CheckRateLimit = super::CheckRateLimit<Test>;
+
+    let rate_limited: TransactionValidityError =
+        InvalidTransaction::Custom(super::RATE_LIMIT_EXCEEDED).into();
+    let free_call = Call::TemplateModule(super::Call::do_something(1));
+    let free_info = free_call.get_dispatch_info();
+    let paid_call = Call::TemplateModule(super::Call::update_something(1));
+    let paid_info = paid_call.get_dispatch_info();
+    assert_eq!(free_info.pays_fee, Pays::No);
+    assert_eq!(paid_info.pays_fee, Pays::Yes);
+
+    // Validate the transaction and apply it the way the block builder does
+    fn submit(who: u64, call: &Call, info: &DispatchInfo) -> Result<(), TransactionValidityError> {
+        CheckRateLimit::new().validate(&who, call, info, 0)?;
+        CheckRateLimit::new().pre_dispatch(&who, call, info, 0)
+    }
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::set_rate_limit(
+            Origin::root(),
+            TestRoles::empty(),
+            Some(super::RateLimit {
+                calls: 3,
+                period: 10,
+            })
+        ));
+
+        // Free calls of the account pending in the pool provide the same tag
+        let provides = |who: u64, calls: u32| vec![("CheckRateLimit", who, 0u64, calls).encode()];
+        let validity = CheckRateLimit::new().validate(&7, &free_call, &free_info, 0);
+        assert_eq!(validity.unwrap().provides, provides(7, 1));
+        assert_ok!(submit(7, &free_call, &free_info));
+        let validity = CheckRateLimit::new().validate(&7, &free_call, &free_info, 0);
+        assert_eq!(validity.unwrap().provides, provides(7, 2));
+        let validity = CheckRateLimit::new().validate(&7, &paid_call, &paid_info, 0);
+        assert!(validity.unwrap().provides.is_empty());
+
+        // Spammer fills the limit of the period
+        for _ in 0..3 {
+            assert_ok!(submit(5, &free_call, &free_info));
+        }
+        assert_eq!(submit(5, &free_call, &free_info), Err(rate_limited));
+        assert_eq!(
+            CheckRateLimit::new().validate(&5, &free_call, &free_info, 0),
+            Err(rate_limited)
+        );
+        assert_eq!(TemplateModule::rate_limit_usage(5), (0, 3));
+
+        // Paid calls and other accounts aren't affected
+        assert_ok!(submit(5, &paid_call, &paid_info));
+        assert_ok!(submit(6, &free_call, &free_info));
+
+        // Validation doesn't count the calls
+        assert_ok!(CheckRateLimit::new().validate(&6, &free_call, &free_info, 0));
+        assert_ok!(CheckRateLimit::new().validate(&6, &free_call, &free_info, 0));
+        assert_eq!(TemplateModule::rate_limit_usage(6), (0, 1));
+
+        // Still limited until the period ends
+        System::set_block_number(9);
+        assert_eq!(submit(5, &free_call, &free_info), Err(rate_limited));
+
+        System::set_block_number(10);
+        assert_ok!(submit(5, &free_call, &free_info));
+        assert_eq!(TemplateModule::rate_limit_usage(5), (10, 1));
+
+        // Removed limit stops the counting
+        assert_ok!(TemplateModule::set_rate_limit(
+            Origin::root(),
+            TestRoles::empty(),
+            None
+        ));
+        for _ in 0..5 {
+            assert_ok!(submit(5, &free_call, &free_info));
+        }
+        assert_eq!(TemplateModule::rate_limit_usage(5), (10, 1));
+    });
+}
+
+#[test]
+fn it_balance() {
+    new_test_ext().execute_with(|| {
+        //total_issuance
+        assert_eq!(Balances::total_issuance(), 100000);
+        assert_eq!(Balances::free_balance(1), 100000);
+        assert_eq!(Balances::free_balance(2), 0);
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            3,
+            TestRoles::USER
+        ));
+
+        assert_ok!(Balances::transfer(Origin::signed(1), 2, 50000));
+
+        assert_eq!(Balances::free_balance(1), 50000);
+        assert_eq!(Balances::free_balance(2), 50000);
+        assert_eq!(Balances::total_issuance(), 100000);
+        assert_ok!(Balances::transfer(Origin::signed(1), 2, 49990));
+        // Account 1 has been removed from balances and dust remaining 10
+        assert_eq!(Balances::total_issuance(), 99990);
+    });
+}
+
+#[test]
+fn it_reserve_role_deposits() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        ROLE_DEPOSITS.with(|deposits| *deposits.borrow_mut() = (2000, 1000));
+
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+        assert_eq!(Balances::reserved_balance(1), 1000);
+        assert_eq!(TemplateModule::deposits(2), Some((1, 1000)));
+        assert_eq!(
+            template_events(),
+            vec![
+                TestEvent::template(super::RawEvent::DepositReserved(1, 2, 1000)),
+                TestEvent::template(super::RawEvent::AccountCreated(1, 2, TestRoles::USER)),
+            ]
+        );
+
+        // The same role keeps the deposit
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+        assert_eq!(Balances::reserved_balance(1), 1000);
+
+        // The deposit follows the role
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::ADMIN
+        ));
+        assert_eq!(Balances::reserved_balance(1), 2000);
+        assert_eq!(TemplateModule::deposits(2), Some((1, 2000)));
+        assert_eq!(
+            template_events(),
+            vec![
+                TestEvent::template(super::RawEvent::AccountCreated(1, 2, TestRoles::USER)),
+                TestEvent::template(super::RawEvent::DepositUnreserved(1, 2, 1000)),
+                TestEvent::template(super::RawEvent::DepositReserved(1, 2, 2000)),
+                TestEvent::template(super::RawEvent::AccountCreated(1, 2, TestRoles::ADMIN)),
+            ]
+        );
+
+        assert_ok!(TemplateModule::account_disable(Origin::signed(1), 2));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(TemplateModule::deposits(2), None);
+        assert_eq!(
+            template_events(),
+            vec![
+                TestEvent::template(super::RawEvent::DepositUnreserved(1, 2, 2000)),
+                TestEvent::template(super::RawEvent::AccountDisabled(1, 2)),
+            ]
+        );
+
+        // Nothing is changed if the sponsor can't pay the deposit
+        ROLE_DEPOSITS.with(|deposits| *deposits.borrow_mut() = (200000, 1000));
+        assert_noop!(
+            TemplateModule::account_add(Origin::signed(1), 3, TestRoles::ADMIN),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+    });
+}
+
+#[test]
+fn it_dispatchable_weight() {
+    // pre-dispatch weights
+    fn assert_dispatch(call: crate::Call<Test>, weight: Weight, pay: Pays) -> DispatchInfo {
+        let dispatch_info = call.get_dispatch_info();
+
+        assert_eq!(dispatch_info.weight, weight);
+        assert_eq!(dispatch_info.pays_fee, pay);
+        dispatch_info
+    }
+    let call = crate::Call::<Test>::account_add(3, TestRoles::ADMIN);
+    assert_dispatch(call, <() as super::WeightInfo>::account_add(), Pays::Yes);
+
+    let call = crate::Call::<Test>::update_something(3);
+    assert_dispatch(
+        call,
+        <() as super::WeightInfo>::update_something(),
+        Pays::Yes,
+    );
+
+    // post-dispatch weights
+    fn assert_call(
+        origin: Origin,
+        call: crate::Call<Test>,
+        weight: Weight,
+        pay: Pays,
+    ) -> DispatchResultWithPostInfo {
+        let dispatch_info = call.get_dispatch_info();
+        let dispatch_post_info = call.dispatch_bypass_filter(origin)?;
+
+        assert_eq!(dispatch_post_info.pays_fee(&dispatch_info), pay);
+        assert_eq!(
+            dispatch_post_info.calc_actual_weight(&dispatch_info),
+            weight
+        );
+        Ok(dispatch_post_info)
+    }
+
+    new_test_ext().execute_with(|| {
+        let call = crate::Call::<Test>::update_something(300);
+        assert_ok!(assert_call(
+            Origin::signed(1),
+            call,
+            <() as super::WeightInfo>::update_something(),
+            Pays::Yes
+        ));
+
+        let call = crate::Call::<Test>::update_something(100);
+        assert_ok!(assert_call(
+            Origin::signed(1),
+            call,
+            <() as super::WeightInfo>::update_something(),
+            Pays::No
+        ));
+
+        let call = crate::Call::<Test>::account_add(3, TestRoles::ADMIN);
+        assert_ok!(assert_call(
+            Origin::signed(1),
+            call,
+            <() as super::WeightInfo>::account_add(),
+            Pays::Yes
+        ));
+
+        let call = crate::Call::<Test>::account_disable(3);
+        assert_ok!(assert_call(
+            Origin::signed(1),
+            call,
+            <() as super::WeightInfo>::account_disable(),
+            Pays::Yes
+        ));
+
+        let call = crate::Call::<Test>::update_something(300);
+        assert_noop!(
+            assert_call(Origin::signed(2), call, 0, Pays::Yes),
+            Error::NotAuthorized
+        );
+    });
+}
+
+#[test]
+fn it_ensure_admin_origin() {
+    use frame_support::traits::EnsureOrigin;
+    type EnsureAdmin = super::EnsureAdmin<Test>;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+        assert_eq!(EnsureAdmin::ensure_origin(Origin::signed(1)).ok(), Some(1));
+        assert!(EnsureAdmin::ensure_origin(Origin::signed(2)).is_err());
+        assert!(EnsureAdmin::ensure_origin(Origin::signed(3)).is_err());
+        assert!(EnsureAdmin::ensure_origin(Origin::root()).is_err());
+    });
+}
+
+fn stale_accounts_report(
+    block_number: u64,
+    accounts: Vec<u64>,
+    signer: u64,
+) -> (super::StaleAccountsPayloadOf<Test>, TestSignature) {
+    use codec::Encode;
+    let payload = super::StaleAccountsPayload {
+        block_number,
+        accounts,
+        public: UintAuthorityId(signer),
+    };
+    let signature = TestSignature(signer, payload.encode());
+    (payload, signature)
+}
+
+#[test]
+fn it_offchain_worker_reports_stale_accounts() {
+    use codec::Decode;
+    use frame_support::traits::OffchainWorker;
+    use sp_core::offchain::{testing, OffchainExt, TransactionPoolExt};
+
+    let (offchain, _state) = testing::TestOffchainExt::new();
+    let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+    let mut t = new_test_ext();
+    t.register_extension(OffchainExt::new(offchain));
+    t.register_extension(TransactionPoolExt::new(pool));
+    // Keystore holds the key of the admin account
+    UintAuthorityId::set_all_keys(vec![1]);
+
+    t.execute_with(|| {
+        Timestamp::set_timestamp(1000);
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            3,
+            TestRoles::USER
+        ));
+        Timestamp::set_timestamp(1000 + StaleAccountAge::get());
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            4,
+            TestRoles::USER
+        ));
+
+        // Not stale yet
+        TemplateModule::offchain_worker(1);
+        assert!(pool_state.read().transactions.is_empty());
+
+        Timestamp::set_timestamp(2000 + StaleAccountAge::get());
+        TemplateModule::offchain_worker(1);
+        let tx = pool_state.write().transactions.pop().unwrap();
+        assert!(pool_state.read().transactions.is_empty());
+        let tx = Extrinsic::decode(&mut &*tx).unwrap();
+        assert_eq!(tx.signature, None);
+        match tx.call {
+            super::Call::report_stale_accounts(mut payload, signature) => {
+                payload.accounts.sort_unstable();
+                let (expected, _) = stale_accounts_report(1, vec![2, 3], 1);
+                assert_eq!(payload, expected);
+                assert_eq!(signature.0, 1);
+            }
+            _ => panic!("unexpected call"),
+        }
+    });
+}
+
+#[test]
+fn it_report_stale_accounts() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Timestamp::set_timestamp(1000);
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+        Timestamp::set_timestamp(2000 + StaleAccountAge::get());
+        events();
+
+        // Admin and unknown accounts are never flagged
+        let (payload, signature) = stale_accounts_report(1, vec![1, 2, 5], 1);
+        assert_noop!(
+            TemplateModule::report_stale_accounts(
+                Origin::signed(1),
+                payload.clone(),
+                signature.clone()
+            ),
+            frame_support::error::BadOrigin
+        );
+        assert_ok!(TemplateModule::report_stale_accounts(
+            Origin::none(),
+            payload,
+            signature
+        ));
+        assert_eq!(
+            events(),
+            vec![TestEvent::template(super::RawEvent::AccountFlagged(2))]
+        );
+        assert_eq!(
+            TemplateModule::stale_accounts(2),
+            Some(2000 + StaleAccountAge::get())
+        );
+        assert_eq!(TemplateModule::stale_accounts(1), None);
+        assert_eq!(TemplateModule::next_report_at(), 1 + ReportInterval::get());
+
+        // Renewed by the admin
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+        assert_eq!(TemplateModule::stale_accounts(2), None);
+        assert!(!TemplateModule::is_stale(
+            &TemplateModule::account_registry(2),
+            Timestamp::get()
+        ));
+    });
+}
+
+#[test]
+fn it_validate_unsigned_stale_accounts_report() {
+    use frame_support::unsigned::ValidateUnsigned;
+    use sp_runtime::transaction_validity::{InvalidTransaction, TransactionSource};
+
+    let validate = |payload, signature| {
+        TemplateModule::validate_unsigned(
+            TransactionSource::External,
+            &super::Call::report_stale_accounts(payload, signature),
+        )
+    };
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(5);
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+
+        let (payload, signature) = stale_accounts_report(5, vec![2], 1);
+        let valid = validate(payload.clone(), signature).unwrap();
+        assert_eq!(valid.priority, UnsignedPriority::get());
+
+        // Wrong signature
+        let (_, signature) = stale_accounts_report(4, vec![2], 1);
+        assert_eq!(
+            validate(payload, signature),
+            InvalidTransaction::BadProof.into()
+        );
+        // Signed by non-admin
+        let (payload, signature) = stale_accounts_report(5, vec![2], 2);
+        assert_eq!(
+            validate(payload, signature),
+            InvalidTransaction::BadProof.into()
+        );
+        // Empty report
+        let (payload, signature) = stale_accounts_report(5, vec![], 1);
+        assert_eq!(
+            validate(payload, signature),
+            InvalidTransaction::Call.into()
+        );
+        // Report from the future
+        let (payload, signature) = stale_accounts_report(6, vec![2], 1);
+        assert_eq!(
+            validate(payload, signature),
+            InvalidTransaction::Future.into()
+        );
+
+        // Report is accepted once per interval
+        let (payload, signature) = stale_accounts_report(5, vec![2], 1);
+        assert_ok!(TemplateModule::report_stale_accounts(
+            Origin::none(),
+            payload,
+            signature
+        ));
+        System::set_block_number(6);
+        let (payload, signature) = stale_accounts_report(6, vec![2], 1);
+        assert_eq!(
+            validate(payload, signature),
+            InvalidTransaction::Stale.into()
+        );
+    });
+}
+
+fn metadata(name: &[u8], external_id: &[u8]) -> super::AccountMetadata<sp_core::H256> {
+    super::AccountMetadata {
+        name: name.to_vec(),
+        external_id: external_id.to_vec(),
+        json_hash: Some(sp_core::H256::repeat_byte(1)),
+    }
+}
+
+#[test]
+fn it_set_account_metadata() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+        events();
+
+        let deposit = MetadataDepositBase::get() + 8 * MetadataDepositPerByte::get();
+        assert_ok!(TemplateModule::account_set_metadata(
+            Origin::signed(1),
+            2,
+            metadata(b"name", b"id-1")
+        ));
+        assert_eq!(Balances::reserved_balance(1), deposit);
+        let record = TemplateModule::metadata(2).unwrap();
+        assert_eq!(record.metadata, metadata(b"name", b"id-1"));
+        assert_eq!(record.depositor, 1);
+        assert_eq!(
+            template_events(),
+            vec![TestEvent::template(super::RawEvent::MetadataSet(
+                1, 2, deposit
+            ))]
+        );
+
+        // Replace metadata, the previous deposit is refunded
+        let deposit = MetadataDepositBase::get() + 2 * MetadataDepositPerByte::get();
+        assert_ok!(TemplateModule::account_set_metadata(
+            Origin::signed(1),
+            2,
+            metadata(b"", b"id")
+        ));
+        assert_eq!(Balances::reserved_balance(1), deposit);
+
+        // Disabling the account refunds the deposit
+        assert_ok!(TemplateModule::account_disable(Origin::signed(1), 2));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(TemplateModule::metadata(2), None);
+    });
+}
+
+#[test]
+fn it_clear_account_metadata() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+        assert_noop!(
+            TemplateModule::account_clear_metadata(Origin::signed(1), 2),
+            Error::NoMetadata
+        );
+        assert_ok!(TemplateModule::account_set_metadata(
+            Origin::signed(1),
+            2,
+            metadata(b"name", b"")
+        ));
+        assert_noop!(
+            TemplateModule::account_clear_metadata(Origin::signed(2), 2),
+            Error::NotAuthorized
+        );
+        events();
+
+        let deposit = Balances::reserved_balance(1);
+        assert_ok!(TemplateModule::account_clear_metadata(Origin::signed(1), 2));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(TemplateModule::metadata(2), None);
+        assert_eq!(
+            template_events(),
+            vec![TestEvent::template(super::RawEvent::MetadataCleared(
+                2, 1, deposit
+            ))]
+        );
+    });
+}
+
+#[test]
+fn it_try_set_invalid_metadata() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::USER
+        ));
+        let too_long = vec![0u8; MaxMetadataLen::get() as usize + 1];
+
+        assert_noop!(
+            TemplateModule::account_set_metadata(Origin::signed(1), 2, metadata(&too_long, b"")),
+            Error::MetadataTooLong
+        );
+        assert_noop!(
+            TemplateModule::account_set_metadata(Origin::signed(1), 2, metadata(b"", &too_long)),
+            Error::MetadataTooLong
+        );
+        assert_noop!(
+            TemplateModule::account_set_metadata(Origin::signed(1), 3, metadata(b"", b"")),
+            Error::NotExists
+        );
+        assert_noop!(
+            TemplateModule::account_set_metadata(Origin::signed(2), 2, metadata(b"", b"")),
+            Error::NotAuthorized
+        );
+        // Admin can't afford the deposit
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            3,
+            TestRoles::ADMIN
+        ));
+        assert_noop!(
+            TemplateModule::account_set_metadata(Origin::signed(3), 2, metadata(b"", b"")),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+    });
+}
+
+#[test]
+fn it_approve_role_request() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Timestamp::set_timestamp(5000);
+        assert_ok!(Balances::transfer(Origin::signed(1), 2, 10000));
+        events();
+
+        assert_ok!(TemplateModule::request_role(
+            Origin::signed(2),
+            TestRoles::USER,
+            b"hello".to_vec()
+        ));
+        assert_eq!(Balances::reserved_balance(2), RoleRequestDeposit::get());
+        assert_eq!(
+            TemplateModule::pending_requests(2),
+            Some(super::RoleRequest {
+                role: TestRoles::USER,
+                note: b"hello".to_vec(),
+                deposit: RoleRequestDeposit::get(),
+                create_time: 5000,
+            })
+        );
+        assert_noop!(
+            TemplateModule::request_role(Origin::signed(2), TestRoles::USER, vec![]),
+            Error::AlreadyRequested
+        );
+        assert_noop!(
+            TemplateModule::approve_request(Origin::signed(2), 2),
+            Error::NotAuthorized
+        );
+
+        assert_ok!(TemplateModule::approve_request(Origin::signed(1), 2));
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(TemplateModule::pending_requests(2), None);
+        assert!(TemplateModule::account_registry(2).is_enable());
+        assert_eq!(TemplateModule::account_registry(2).create_time, 5000);
+        assert_eq!(
+            template_events(),
+            vec![
+                TestEvent::template(super::RawEvent::RoleRequested(
+                    2,
+                    TestRoles::USER,
+                    RoleRequestDeposit::get()
+                )),
+                TestEvent::template(super::RawEvent::RoleRequestApproved(1, 2, TestRoles::USER)),
+                TestEvent::template(super::RawEvent::AccountCreated(1, 2, TestRoles::USER)),
+            ]
+        );
+        assert_noop!(
+            TemplateModule::approve_request(Origin::signed(1), 2),
+            Error::NoRequest
+        );
+    });
+}
+
+#[test]
+fn it_try_approve_own_request_dropping_admin() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::request_role(
+            Origin::signed(1),
+            TestRoles::USER,
+            vec![]
+        ));
+        assert_noop!(
+            TemplateModule::approve_request(Origin::signed(1), 1),
+            Error::CannotDisableSelf
+        );
+        assert!(TemplateModule::account_is_admin(&1));
+        assert_eq!(TemplateModule::admins(), vec![1]);
+    });
+}
+
+#[test]
+fn it_reject_role_request() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(Balances::transfer(Origin::signed(1), 2, 10000));
+        assert_noop!(
+            TemplateModule::request_role(Origin::signed(3), TestRoles::USER, vec![]),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+        assert_noop!(
+            TemplateModule::request_role(
+                Origin::signed(2),
+                TestRoles::USER,
+                vec![0; MaxMetadataLen::get() as usize + 1]
+            ),
+            Error::MetadataTooLong
+        );
+        assert_ok!(TemplateModule::request_role(
+            Origin::signed(2),
+            TestRoles::ADMIN,
+            vec![]
+        ));
+        events();
+
+        let issuance = Balances::total_issuance();
+        assert_ok!(TemplateModule::reject_request(Origin::signed(1), 2));
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::free_balance(2), 10000 - RoleRequestDeposit::get());
+        assert_eq!(
+            Balances::total_issuance(),
+            issuance - RoleRequestDeposit::get()
+        );
+        assert!(!TemplateModule::account_registry(2).is_enable());
+        assert_eq!(
+            events(),
+            vec![TestEvent::template(super::RawEvent::RoleRequestRejected(
+                1,
+                2,
+                RoleRequestDeposit::get()
+            ))]
+        );
+    });
+}
+
+#[test]
+fn it_force_manage_registry() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            TemplateModule::force_account_add(Origin::signed(1), 2, TestRoles::ADMIN),
+            frame_support::error::BadOrigin
+        );
+        assert_noop!(
+            TemplateModule::force_account_disable(Origin::signed(1), 1),
+            frame_support::error::BadOrigin
+        );
+        assert_noop!(
+            TemplateModule::force_account_disable(Origin::root(), 2),
+            Error::NotExists
+        );
+
+        assert_ok!(TemplateModule::force_account_add(
+            Origin::root(),
+            2,
+            TestRoles::ADMIN
+        ));
+        assert!(TemplateModule::account_is_admin(&2));
+        // Root can disable the last original admin
+        assert_ok!(TemplateModule::force_account_disable(Origin::root(), 1));
+        assert!(!TemplateModule::account_registry(1).is_enable());
+        assert_eq!(
+            events(),
+            vec![
+                TestEvent::template(super::RawEvent::AccountForceCreated(2, TestRoles::ADMIN)),
+                TestEvent::template(super::RawEvent::AccountForceDisabled(1)),
+            ]
+        );
+    });
+}
+
+#[test]
+fn it_force_unlock() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::account_transfer_and_lock(
+            Origin::signed(1),
+            2,
+            10000
+        ));
+        assert_noop!(
+            TemplateModule::force_unlock(Origin::signed(1), 2),
+            frame_support::error::BadOrigin
+        );
+        assert_ok!(TemplateModule::set_lock(
+            Origin::signed(1),
+            2,
+            *b"penalty ",
+            2000,
+            super::LockReasons::ExceptFee
+        ));
+        events();
+
+        // Every lock category is removed
+        assert_ok!(TemplateModule::force_unlock(Origin::root(), 2));
+        assert_ok!(Balances::transfer(Origin::signed(2), 3, 5000));
+        assert_eq!(
+            events()[0],
+            TestEvent::template(super::RawEvent::BalanceUnlocked(2))
+        );
+        assert!(TemplateModule::account_locks(&2).is_empty());
+        assert!(Balances::locks(2).is_empty());
+        assert_noop!(
+            TemplateModule::force_unlock(Origin::root(), 2),
+            Error::LockNotFound
+        );
+    });
+}
+
+#[test]
+fn it_keep_admins_in_sync() {
+    use frame_support::StorageValue;
+
+    new_test_ext().execute_with(|| {
+        assert_eq!(TemplateModule::admins(), vec![1]);
+
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            5,
+            TestRoles::ADMIN
+        ));
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            3,
+            TestRoles::ADMIN
+        ));
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            4,
+            TestRoles::USER
+        ));
+        assert_eq!(TemplateModule::admins(), vec![1, 3, 5]);
+        assert_eq!(ADMINS.with(|a| a.borrow().clone()), vec![1, 3, 5]);
+
+        // Downgraded and disabled admins leave the set
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            3,
+            TestRoles::USER
+        ));
+        assert_ok!(TemplateModule::account_disable(Origin::signed(1), 5));
+        assert_eq!(TemplateModule::admins(), vec![1]);
+        assert_eq!(ADMINS.with(|a| a.borrow().clone()), vec![1]);
+
+        // Rebuild the set from the registry
+        super::Admins::<Test>::kill();
+        TemplateModule::initialize_admins();
+        assert_eq!(TemplateModule::admins(), vec![1]);
+    });
+}
+
+#[test]
+fn it_root_dispatch() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let call = Box::new(Call::TemplateModule(super::Call::force_account_add(
+            2,
+            TestRoles::ADMIN,
+        )));
+        assert_noop!(
+            TemplateModule::root_dispatch(Origin::signed(1), call.clone()),
+            frame_support::error::BadOrigin
+        );
+
+        assert_ok!(TemplateModule::root_dispatch(Origin::root(), call));
+        assert!(TemplateModule::account_is_admin(&2));
+        assert_eq!(
+            events(),
+            vec![
+                TestEvent::template(super::RawEvent::AccountForceCreated(2, TestRoles::ADMIN)),
+                TestEvent::template(super::RawEvent::RootDispatched(Ok(()))),
+            ]
+        );
+
+        // Failed call result is reported by the event
+        let call = Box::new(Call::TemplateModule(super::Call::force_account_disable(3)));
+        assert_ok!(TemplateModule::root_dispatch(Origin::root(), call));
+        // Error message is skipped by the event encoding
+        let error = match sp_runtime::DispatchError::from(Error::NotExists) {
+            sp_runtime::DispatchError::Module { index, error, .. } => {
+                sp_runtime::DispatchError::Module {
+                    index,
+                    error,
+                    message: None,
+                }
+            }
+            other => other,
+        };
+        assert_eq!(
+            events(),
+            vec![TestEvent::template(super::RawEvent::RootDispatched(Err(
+                error
+            )))]
+        );
+    });
+}
+
+#[test]
+fn it_add_accounts_batch() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::account_add_batch(
+            Origin::signed(1),
+            vec![(2, TestRoles::USER), (3, TestRoles::ADMIN)]
+        ));
+        assert!(TemplateModule::account_registry(2).is_enable());
+        assert!(TemplateModule::account_is_admin(&3));
+        assert_eq!(
+            events(),
+            vec![
+                TestEvent::template(super::RawEvent::AccountCreated(1, 2, TestRoles::USER)),
+                TestEvent::template(super::RawEvent::AccountCreated(1, 3, TestRoles::ADMIN)),
+            ]
+        );
+
+        assert_noop!(
+            TemplateModule::account_add_batch(Origin::signed(2), vec![(4, TestRoles::USER)]),
+            Error::NotAuthorized
+        );
+        assert_noop!(
+            TemplateModule::account_add_batch(
+                Origin::signed(1),
+                (4..8).map(|acc| (acc, TestRoles::USER)).collect()
+            ),
+            Error::BatchTooLarge
+        );
+    });
+}
+
+#[test]
+fn it_disable_accounts_batch_atomically() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::account_add_batch(
+            Origin::signed(1),
+            vec![(2, TestRoles::USER), (3, TestRoles::USER)]
+        ));
+        events();
+
+        // Nothing is disabled if any account fails
+        assert_noop!(
+            TemplateModule::account_disable_batch(Origin::signed(1), vec![2, 4]),
+            Error::NotExists
+        );
+        assert_noop!(
+            TemplateModule::account_disable_batch(Origin::signed(1), vec![2, 1]),
+            Error::CannotDisableSelf
+        );
+        assert!(TemplateModule::account_registry(2).is_enable());
+
+        assert_ok!(TemplateModule::account_disable_batch(
+            Origin::signed(1),
+            vec![2, 3]
+        ));
+        assert!(!TemplateModule::account_registry(2).is_enable());
+        assert!(!TemplateModule::account_registry(3).is_enable());
+        assert_eq!(
+            events(),
+            vec![
+                TestEvent::template(super::RawEvent::AccountDisabled(1, 2)),
+                TestEvent::template(super::RawEvent::AccountDisabled(1, 3)),
+            ]
+        );
+    });
+}
+
+#[test]
+fn it_batch_weight_is_linear() {
+    let weight = |n: u64| {
+        crate::Call::<Test>::account_add_batch((0..n).map(|acc| (acc, TestRoles::USER)).collect())
+            .get_dispatch_info()
+            .weight
+    };
+    assert_eq!(weight(2) - weight(1), weight(3) - weight(2));
+    assert!(weight(2) > weight(1));
+}
+
+#[test]
+fn it_apply_reap_policy() {
+    use super::ReapPolicy;
+
+    // Reap `account` by transferring its whole balance to the admin
+    fn reap(account: u64) {
+        let balance = Balances::free_balance(account);
+        assert_ok!(Balances::transfer(Origin::signed(account), 1, balance));
+        assert_eq!(Balances::free_balance(account), 0);
+    }
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        for account in 2..5 {
+            assert_ok!(Balances::transfer(Origin::signed(1), account, 10000));
+        }
+        assert_ok!(TemplateModule::account_add_batch(
+            Origin::signed(1),
+            vec![
+                (2, TestRoles::USER),
+                (3, TestRoles::ADMIN),
+                (4, TestRoles::USER)
+            ]
+        ));
+
+        REAP_POLICY.with(|policy| *policy.borrow_mut() = ReapPolicy::KeepAdmins);
+        events();
+        reap(2);
+        reap(3);
+        assert!(!TemplateModule::account_registry(2).is_enable());
+        assert!(TemplateModule::account_is_admin(&3));
+        let reaped: Vec<_> = events()
+            .into_iter()
+            .filter(|evt| matches!(evt, TestEvent::template(super::RawEvent::AccountReaped(..))))
+            .collect();
+        assert_eq!(
+            reaped,
+            vec![
+                TestEvent::template(super::RawEvent::AccountReaped(2, false)),
+                TestEvent::template(super::RawEvent::AccountReaped(3, true)),
+            ]
+        );
+
+        REAP_POLICY.with(|policy| *policy.borrow_mut() = ReapPolicy::Keep);
+        reap(4);
+        assert!(TemplateModule::account_registry(4).is_enable());
+    });
+}
+
+#[test]
+fn it_keep_last_admin_on_reap() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::account_add(
+            Origin::signed(1),
+            2,
+            TestRoles::ADMIN
+        ));
+        // Default policy removes admins while other admins exist
+        assert_ok!(Balances::transfer(Origin::signed(1), 2, 100000));
+        assert!(!TemplateModule::account_registry(1).is_enable());
+        assert_eq!(TemplateModule::admins(), vec![2]);
+
+        assert_ok!(Balances::transfer(Origin::signed(2), 3, 100000));
+        assert!(TemplateModule::account_is_admin(&2));
+        assert_eq!(TemplateModule::admins(), vec![2]);
+    });
+}
+
+#[test]
+fn it_act_by_delegate() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            TemplateModule::add_delegate(Origin::signed(1), 10, 0, 10),
+            Error::InvalidData
+        );
+        assert_noop!(
+            TemplateModule::add_delegate(Origin::signed(1), 10, 0x80, 10),
+            Error::InvalidData
+        );
+        assert_noop!(
+            TemplateModule::add_delegate(Origin::signed(1), 10, super::PERMISSION_ACCOUNT_ADD, 1),
+            Error::InvalidData
+        );
+        assert_noop!(
+            TemplateModule::add_delegate(Origin::signed(2), 10, super::PERMISSION_ACCOUNT_ADD, 10),
+            Error::NotAuthorized
+        );
+        assert_ok!(TemplateModule::add_delegate(
+            Origin::signed(1),
+            10,
+            super::PERMISSION_ACCOUNT_ADD,
+            10
+        ));
+        events();
+
+        assert_ok!(TemplateModule::delegated_account_add(
+            Origin::signed(10),
+            1,
+            2,
+            TestRoles::USER
+        ));
+        assert!(TemplateModule::account_registry(2).is_enable());
+        assert_eq!(
+            events(),
+            vec![
+                TestEvent::template(super::RawEvent::AccountCreated(1, 2, TestRoles::USER)),
+                TestEvent::template(super::RawEvent::DelegateActed(1, 10)),
+            ]
+        );
+
+        // ADMIN role can't be granted by the delegate, to itself or anyone else
+        assert_noop!(
+            TemplateModule::delegated_account_add(Origin::signed(10), 1, 10, TestRoles::ADMIN),
+            Error::NotAuthorized
+        );
+        assert_noop!(
+            TemplateModule::delegated_account_add(
+                Origin::signed(10),
+                1,
+                3,
+                TestRoles::ADMIN | TestRoles::USER
+            ),
+            Error::NotAuthorized
+        );
+
+        // Permission to disable is not delegated
+        assert_noop!(
+            TemplateModule::delegated_account_disable(Origin::signed(10), 1, 2),
+            Error::NotDelegated
+        );
+        // No delegation from account 2
+        assert_noop!(
+            TemplateModule::delegated_account_add(Origin::signed(10), 2, 3, TestRoles::USER),
+            Error::NotAuthorized
+        );
+
+        assert_ok!(TemplateModule::add_delegate(
+            Origin::signed(1),
+            10,
+            super::PERMISSION_ACCOUNT_ADD | super::PERMISSION_ACCOUNT_DISABLE,
+            10
+        ));
+        assert_noop!(
+            TemplateModule::delegated_account_disable(Origin::signed(10), 1, 1),
+            Error::CannotDisableSelf
+        );
+        assert_ok!(TemplateModule::delegated_account_disable(
+            Origin::signed(10),
+            1,
+            2
+        ));
+        assert!(!TemplateModule::account_registry(2).is_enable());
+    });
+}
+
+#[test]
+fn it_expire_and_remove_delegation() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(TemplateModule::add_delegate(
+            Origin::signed(1),
+            10,
+            super::PERMISSION_ACCOUNT_ADD,
+            5
+        ));
+
+        System::set_block_number(5);
+        assert_noop!(
+            TemplateModule::delegated_account_add(Origin::signed(10), 1, 2, TestRoles::USER),
+            Error::DelegationExpired
+        );
+
+        assert_ok!(TemplateModule::remove_delegate(Origin::signed(1), 10));
+        assert_eq!(TemplateModule::delegations(1, 10), None);
+        assert_noop!(
+            TemplateModule::remove_delegate(Origin::signed(1), 10),
+            Error::NotDelegated
+        );
+
+        // Delegations are removed with the admin account
+        assert_ok!(TemplateModule::add_delegate(
+            Origin::signed(1),
+            10,
+            super::PERMISSION_ACCOUNT_ADD,
+            10
+        ));
+        assert_ok!(TemplateModule::force_account_disable(Origin::root(), 1));
+        assert_eq!(TemplateModule::delegations(1, 10), None);
     });
 }
@@ -0,0 +1,223 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+//! Proof-of-authority validators set.
+//!
+//! The pallet keeps the list of validators in storage and provides it to `pallet_session`
+//! through `SessionManager` implementation. The list may be changed only by `ManagerOrigin`,
+//! the changes are applied at the next session.
+//! Reported offenders are disabled and removed from the list by `OnOffenceHandler` implementation.
+
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+    sp_runtime::{traits::Convert, Perbill},
+    traits::{EnsureOrigin, Get, ValidatorRegistration},
+    weights::Weight,
+};
+use pallet_session::historical::IdentificationTuple;
+use sp_staking::{
+    offence::{OffenceDetails, OnOffenceHandler},
+    SessionIndex,
+};
+use sp_std::prelude::*;
+
+mod default_weight;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+pub trait Trait: frame_system::Trait + pallet_session::Trait {
+    /// Because this pallet emits events, it depends on the runtime's definition of an event.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Origin allowed to add or remove validators.
+    type ManagerOrigin: EnsureOrigin<Self::Origin>;
+    /// Validators set can't be reduced below this value.
+    type MinValidators: Get<u32>;
+    type WeightInfo: WeightInfo;
+}
+
+pub trait WeightInfo {
+    fn add_validator() -> Weight;
+    fn remove_validator() -> Weight;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as ValidatorSet {
+        /// Validators to be used by the next sessions.
+        Validators get(fn validators) config(): Vec<T::ValidatorId>;
+        /// Validators set has been changed since the last session start.
+        Changed: bool;
+    }
+    add_extra_genesis {
+        build(|config: &GenesisConfig<T>| {
+            // Provide initial validators to pallet_session on genesis
+            Changed::put(!config.validators.is_empty());
+        });
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        ValidatorId = <T as pallet_session::Trait>::ValidatorId,
+    {
+        /// New validator has been added. Becomes active since the next but one session. [validator]
+        ValidatorAdded(ValidatorId),
+        /// Validator has been removed. Becomes inactive since the next but one session. [validator]
+        ValidatorRemoved(ValidatorId),
+        /// Offender has been disabled for the current session. [validator, removed from the set]
+        OffenderDisabled(ValidatorId, bool),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Validator is already in the set
+        AlreadyValidator,
+        /// Validator is not in the set
+        NotValidator,
+        /// Validators set can't be reduced below `MinValidators`
+        TooFewValidators,
+        /// Validator has no session keys
+        NoSessionKeys,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        const MinValidators: u32 = T::MinValidators::get();
+
+        fn deposit_event() = default;
+
+        /// Add new validator. Validator has to set its session keys
+        /// with `pallet_session::set_keys` before.
+        #[weight = <T as Trait>::WeightInfo::add_validator()]
+        pub fn add_validator(origin, validator: T::ValidatorId) -> dispatch::DispatchResult {
+            T::ManagerOrigin::ensure_origin(origin)?;
+            ensure!(
+                <pallet_session::Module<T> as ValidatorRegistration<_>>::is_registered(&validator),
+                Error::<T>::NoSessionKeys
+            );
+
+            Validators::<T>::try_mutate(|validators| -> dispatch::DispatchResult {
+                ensure!(!validators.contains(&validator), Error::<T>::AlreadyValidator);
+                validators.push(validator.clone());
+                Ok(())
+            })?;
+            Changed::put(true);
+
+            Self::deposit_event(RawEvent::ValidatorAdded(validator));
+            Ok(())
+        }
+
+        /// Remove validator. Fails if validators set became less than `MinValidators`.
+        #[weight = <T as Trait>::WeightInfo::remove_validator()]
+        pub fn remove_validator(origin, validator: T::ValidatorId) -> dispatch::DispatchResult {
+            T::ManagerOrigin::ensure_origin(origin)?;
+
+            Validators::<T>::try_mutate(|validators| -> dispatch::DispatchResult {
+                let index = validators
+                    .iter()
+                    .position(|v| *v == validator)
+                    .ok_or(Error::<T>::NotValidator)?;
+                ensure!(
+                    validators.len() as u32 > T::MinValidators::get(),
+                    Error::<T>::TooFewValidators
+                );
+                validators.remove(index);
+                Ok(())
+            })?;
+            Changed::put(true);
+
+            Self::deposit_event(RawEvent::ValidatorRemoved(validator));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> pallet_session::SessionManager<T::ValidatorId> for Module<T> {
+    /// Provide validators set if it has been changed, otherwise keep the current one.
+    fn new_session(_new_index: SessionIndex) -> Option<Vec<T::ValidatorId>> {
+        if Changed::take() {
+            Some(Self::validators())
+        } else {
+            None
+        }
+    }
+
+    fn end_session(_end_index: SessionIndex) {}
+
+    fn start_session(_start_index: SessionIndex) {}
+}
+
+/// Validators set with full identifications to keep historical session data
+/// used by the key ownership proofs.
+impl<T: Trait + pallet_session::historical::Trait>
+    pallet_session::historical::SessionManager<T::ValidatorId, T::FullIdentification>
+    for Module<T>
+{
+    fn new_session(
+        new_index: SessionIndex,
+    ) -> Option<Vec<(T::ValidatorId, T::FullIdentification)>> {
+        <Self as pallet_session::SessionManager<_>>::new_session(new_index).map(|validators| {
+            validators
+                .into_iter()
+                .filter_map(|v| T::FullIdentificationOf::convert(v.clone()).map(|full| (v, full)))
+                .collect()
+        })
+    }
+
+    fn end_session(end_index: SessionIndex) {
+        <Self as pallet_session::SessionManager<_>>::end_session(end_index)
+    }
+
+    fn start_session(start_index: SessionIndex) {
+        <Self as pallet_session::SessionManager<_>>::start_session(start_index)
+    }
+}
+
+/// Disable offenders in the current session and exclude them from the next sessions.
+/// Offenders stay in the set if it would become less than `MinValidators`.
+/// `OffenderDisabled` is emitted only for offenders of the current session, offenders
+/// which are not active anymore are just removed from the set.
+impl<T: Trait + pallet_session::historical::Trait>
+    OnOffenceHandler<T::AccountId, IdentificationTuple<T>, Weight> for Module<T>
+{
+    fn on_offence(
+        offenders: &[OffenceDetails<T::AccountId, IdentificationTuple<T>>],
+        _slash_fraction: &[Perbill],
+        _session: SessionIndex,
+    ) -> Result<Weight, ()> {
+        for details in offenders {
+            let (validator, _) = &details.offender;
+            // fails for offenders which are not in the current session
+            let disabled = pallet_session::Module::<T>::disable(validator).is_ok();
+
+            let removed = Validators::<T>::mutate(|validators| {
+                match validators.iter().position(|v| v == validator) {
+                    Some(index) if validators.len() as u32 > T::MinValidators::get() => {
+                        validators.remove(index);
+                        true
+                    }
+                    _ => false,
+                }
+            });
+            if removed {
+                Changed::put(true);
+            }
+
+            if disabled {
+                Self::deposit_event(RawEvent::OffenderDisabled(validator.clone(), removed));
+            } else if removed {
+                Self::deposit_event(RawEvent::ValidatorRemoved(validator.clone()));
+            }
+        }
+
+        Ok(T::DbWeight::get().reads_writes(3, 4) * offenders.len() as Weight)
+    }
+
+    fn can_report() -> bool {
+        true
+    }
+}
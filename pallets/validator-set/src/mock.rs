@@ -0,0 +1,157 @@
+use crate::{Module, Trait};
+use frame_support::{
+    impl_outer_event, impl_outer_origin, parameter_types,
+    traits::Contains,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::{Header, UintAuthorityId},
+    traits::{BlakeTwo256, ConvertInto, IdentityLookup, OpaqueKeys},
+    KeyTypeId, Perbill, RuntimeAppPublic,
+};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+mod validator_set {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        pallet_session,
+        validator_set<T>,
+    }
+}
+
+// Configure a mock runtime to test the pallet.
+pub type System = system::Module<Test>;
+pub type Session = pallet_session::Module<Test>;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = TestEvent;
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = RocksDbWeight;
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+/// Session handler that ignores any session changes.
+pub struct TestSessionHandler;
+impl pallet_session::SessionHandler<u64> for TestSessionHandler {
+    const KEY_TYPE_IDS: &'static [KeyTypeId] = &[UintAuthorityId::ID];
+    fn on_genesis_session<Ks: OpaqueKeys>(_validators: &[(u64, Ks)]) {}
+    fn on_new_session<Ks: OpaqueKeys>(
+        _changed: bool,
+        _validators: &[(u64, Ks)],
+        _queued_validators: &[(u64, Ks)],
+    ) {
+    }
+    fn on_disabled(_validator_index: usize) {}
+}
+
+parameter_types! {
+    pub const Period: u64 = 10;
+    pub const Offset: u64 = 0;
+    pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(33);
+}
+
+impl pallet_session::Trait for Test {
+    type Event = TestEvent;
+    type ValidatorId = u64;
+    type ValidatorIdOf = ConvertInto;
+    type ShouldEndSession = pallet_session::PeriodicSessions<Period, Offset>;
+    type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
+    type SessionManager = pallet_session::historical::NoteHistoricalRoot<Test, ValidatorSet>;
+    type SessionHandler = TestSessionHandler;
+    type Keys = UintAuthorityId;
+    type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
+    type WeightInfo = ();
+}
+
+impl pallet_session::historical::Trait for Test {
+    type FullIdentification = u64;
+    type FullIdentificationOf = ConvertInto;
+}
+
+pub const ADMIN: u64 = 100;
+
+/// Only `ADMIN` account manages validators
+pub struct Admins;
+impl Contains<u64> for Admins {
+    fn sorted_members() -> Vec<u64> {
+        vec![ADMIN]
+    }
+}
+
+parameter_types! {
+    pub const MinValidators: u32 = 2;
+}
+
+impl Trait for Test {
+    type Event = TestEvent;
+    type ManagerOrigin = system::EnsureSignedBy<Admins, u64>;
+    type MinValidators = MinValidators;
+    type WeightInfo = ();
+}
+
+pub type ValidatorSet = Module<Test>;
+
+static INITIAL_VALIDATORS: [u64; 3] = [1, 2, 3];
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+
+    // Validators set has to be initialized before the session.
+    super::GenesisConfig::<Test> {
+        validators: INITIAL_VALIDATORS.to_vec(),
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    pallet_session::GenesisConfig::<Test> {
+        keys: INITIAL_VALIDATORS
+            .iter()
+            .map(|v| (*v, *v, UintAuthorityId(*v)))
+            .collect(),
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    storage.into()
+}
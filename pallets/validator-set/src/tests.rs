@@ -0,0 +1,191 @@
+use crate::mock::*;
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{testing::UintAuthorityId, DispatchError};
+
+type Error = super::Error<Test>;
+
+// get last events and reset
+fn events() -> Vec<TestEvent> {
+    let evt = System::events()
+        .into_iter()
+        .map(|evt| evt.event)
+        .collect::<Vec<_>>();
+    System::reset_events();
+    evt
+}
+
+#[test]
+fn it_provides_genesis_validators() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(ValidatorSet::validators(), vec![1, 2, 3]);
+        assert_eq!(Session::validators(), vec![1, 2, 3]);
+    });
+}
+
+#[test]
+fn it_adds_validator() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(Session::set_keys(
+            Origin::signed(4),
+            UintAuthorityId(4),
+            vec![]
+        ));
+        System::reset_events();
+        assert_ok!(ValidatorSet::add_validator(Origin::signed(ADMIN), 4));
+        assert_eq!(ValidatorSet::validators(), vec![1, 2, 3, 4]);
+        assert_eq!(
+            events(),
+            vec![TestEvent::validator_set(super::RawEvent::ValidatorAdded(4))]
+        );
+
+        // the new set is queued at the next session and applied at the next but one
+        Session::rotate_session();
+        assert_eq!(Session::validators(), vec![1, 2, 3]);
+        Session::rotate_session();
+        assert_eq!(Session::validators(), vec![1, 2, 3, 4]);
+    });
+}
+
+#[test]
+fn it_requires_session_keys() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ValidatorSet::add_validator(Origin::signed(ADMIN), 4),
+            Error::NoSessionKeys
+        );
+    });
+}
+
+#[test]
+fn it_removes_validator() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ValidatorSet::remove_validator(Origin::signed(ADMIN), 2));
+        assert_eq!(ValidatorSet::validators(), vec![1, 3]);
+
+        Session::rotate_session();
+        Session::rotate_session();
+        assert_eq!(Session::validators(), vec![1, 3]);
+    });
+}
+
+#[test]
+fn it_keeps_min_validators() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ValidatorSet::remove_validator(Origin::signed(ADMIN), 1));
+        assert_noop!(
+            ValidatorSet::remove_validator(Origin::signed(ADMIN), 2),
+            Error::TooFewValidators
+        );
+    });
+}
+
+#[test]
+fn it_rejects_duplicates_and_unknown() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ValidatorSet::add_validator(Origin::signed(ADMIN), 1),
+            Error::AlreadyValidator
+        );
+        assert_noop!(
+            ValidatorSet::remove_validator(Origin::signed(ADMIN), 5),
+            Error::NotValidator
+        );
+    });
+}
+
+#[test]
+fn it_requires_manager_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ValidatorSet::add_validator(Origin::signed(1), 4),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            ValidatorSet::remove_validator(Origin::signed(1), 2),
+            DispatchError::BadOrigin
+        );
+    });
+}
+
+#[test]
+fn it_disables_offender() {
+    use sp_runtime::Perbill;
+    use sp_staking::offence::{OffenceDetails, OnOffenceHandler};
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let offenders = [OffenceDetails {
+            offender: (2, 2),
+            reporters: vec![],
+        }];
+        assert_ok!(<ValidatorSet as OnOffenceHandler<_, _, _>>::on_offence(
+            &offenders,
+            &[Perbill::from_percent(10)],
+            0
+        ));
+
+        assert_eq!(Session::disabled_validators(), vec![1]);
+        assert_eq!(ValidatorSet::validators(), vec![1, 3]);
+        assert_eq!(
+            events(),
+            vec![TestEvent::validator_set(super::RawEvent::OffenderDisabled(
+                2, true
+            ))]
+        );
+
+        // offender is not removed when the set reaches minimal size
+        let offenders = [OffenceDetails {
+            offender: (3, 3),
+            reporters: vec![],
+        }];
+        assert_ok!(<ValidatorSet as OnOffenceHandler<_, _, _>>::on_offence(
+            &offenders,
+            &[Perbill::from_percent(10)],
+            0
+        ));
+        assert_eq!(ValidatorSet::validators(), vec![1, 3]);
+        assert_eq!(
+            events(),
+            vec![TestEvent::validator_set(super::RawEvent::OffenderDisabled(
+                3, false
+            ))]
+        );
+    });
+}
+
+#[test]
+fn it_removes_inactive_offender() {
+    use sp_runtime::Perbill;
+    use sp_staking::offence::{OffenceDetails, OnOffenceHandler};
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(Session::set_keys(
+            Origin::signed(4),
+            UintAuthorityId(4),
+            vec![]
+        ));
+        assert_ok!(ValidatorSet::add_validator(Origin::signed(ADMIN), 4));
+        System::reset_events();
+
+        // validator 4 is not active yet, so it can't be disabled
+        let offenders = [OffenceDetails {
+            offender: (4, 4),
+            reporters: vec![],
+        }];
+        assert_ok!(<ValidatorSet as OnOffenceHandler<_, _, _>>::on_offence(
+            &offenders,
+            &[Perbill::from_percent(10)],
+            0
+        ));
+        assert!(Session::disabled_validators().is_empty());
+        assert_eq!(ValidatorSet::validators(), vec![1, 2, 3]);
+        assert_eq!(
+            events(),
+            vec![TestEvent::validator_set(super::RawEvent::ValidatorRemoved(
+                4
+            ))]
+        );
+    });
+}
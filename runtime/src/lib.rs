@@ -7,18 +7,27 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use codec::Encode;
 use pallet_grandpa::fg_primitives;
 use pallet_grandpa::{AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
-use pallet_template::{prelude::IdentityMultiplierUpdater, AccountOf, ADMIN_ROLE};
+use pallet_session::historical as pallet_session_historical;
+use pallet_template::{
+    prelude::IdentityMultiplierUpdater, AccountOf, CheckRateLimit, EnsureAdmin, ReapPolicy,
+};
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use sp_core::{
+    crypto::KeyTypeId,
+    u32_trait::{_2, _3},
+    OpaqueMetadata,
+};
 use sp_runtime::traits::{
-    BlakeTwo256, Block as BlockT, IdentifyAccount, IdentityLookup, NumberFor, Saturating, Verify,
+    BlakeTwo256, Block as BlockT, ConvertInto, IdentifyAccount, IdentityLookup, NumberFor,
+    OpaqueKeys, Saturating, Verify,
 };
 use sp_runtime::{
     create_runtime_str, generic, impl_opaque_keys,
-    transaction_validity::{TransactionSource, TransactionValidity},
+    transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
     ApplyExtrinsicResult, MultiSignature,
 };
 use sp_std::prelude::*;
@@ -99,10 +108,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("node-template"),
     impl_name: create_runtime_str!("node-template"),
     authoring_version: 1,
-    spec_version: 1,
+    spec_version: 11,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 6,
 };
 
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -204,7 +213,8 @@ impl pallet_grandpa::Trait for Runtime {
     type Event = Event;
     type Call = Call;
 
-    type KeyOwnerProofSystem = ();
+    /// Prove that an authority key belonged to a validator in the past sessions.
+    type KeyOwnerProofSystem = Historical;
 
     type KeyOwnerProof =
         <Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, GrandpaId)>>::Proof;
@@ -214,11 +224,78 @@ impl pallet_grandpa::Trait for Runtime {
         GrandpaId,
     )>>::IdentificationTuple;
 
-    type HandleEquivocation = ();
+    /// Equivocation reports are submitted as offences. The block author is the reporter.
+    type HandleEquivocation =
+        pallet_grandpa::EquivocationHandler<Self::KeyOwnerIdentification, Offences>;
 
     type WeightInfo = ();
 }
 
+parameter_types! {
+    pub const UncleGenerations: BlockNumber = 0;
+}
+
+impl pallet_authorship::Trait for Runtime {
+    type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
+    type UncleGenerations = UncleGenerations;
+    type FilterUncle = ();
+    type EventHandler = ();
+}
+
+parameter_types! {
+    pub OffencesWeightSoftLimit: Weight = Perbill::from_percent(60) * MaximumBlockWeight::get();
+}
+
+impl pallet_offences::Trait for Runtime {
+    type Event = Event;
+    type IdentificationTuple = pallet_session_historical::IdentificationTuple<Self>;
+    /// Offenders are disabled and removed from the validators set.
+    type OnOffenceHandler = ValidatorSet;
+    type WeightSoftLimit = OffencesWeightSoftLimit;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+    Call: From<C>,
+{
+    type Extrinsic = UncheckedExtrinsic;
+    type OverarchingCall = Call;
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+    type Public = <Signature as Verify>::Signer;
+    type Signature = Signature;
+}
+
+parameter_types! {
+    /// Number of blocks in a session. Aura and Grandpa authorities are rotated at session boundaries.
+    pub const SessionPeriod: BlockNumber = 10 * MINUTES;
+    pub const SessionOffset: BlockNumber = 0;
+    pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(17);
+}
+
+impl pallet_session::Trait for Runtime {
+    type Event = Event;
+    /// Validators are identified by their account.
+    type ValidatorId = AccountId;
+    type ValidatorIdOf = ConvertInto;
+    type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+    type NextSessionRotation = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+    /// Validators set is managed by registry admins.
+    /// Historical session data is required to check GRANDPA equivocation proofs.
+    type SessionManager = pallet_session_historical::NoteHistoricalRoot<Self, ValidatorSet>;
+    /// Aura and Grandpa get notified about new session keys.
+    type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+    type Keys = opaque::SessionKeys;
+    type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
+    type WeightInfo = ();
+}
+
+impl pallet_session_historical::Trait for Runtime {
+    type FullIdentification = AccountId;
+    type FullIdentificationOf = ConvertInto;
+}
+
 parameter_types! {
     pub const MinimumPeriod: u64 = SLOT_DURATION / 2;
 }
@@ -261,22 +338,97 @@ impl pallet_transaction_payment::Trait for Runtime {
     type FeeMultiplierUpdate = IdentityMultiplierUpdater;
 }
 
-impl pallet_sudo::Trait for Runtime {
+parameter_types! {
+    pub const CouncilMotionDuration: BlockNumber = 3 * DAYS;
+    pub const CouncilMaxProposals: u32 = 100;
+    pub const CouncilMaxMembers: u32 = 100;
+}
+
+/// Council of the registry admins.
+type CouncilCollective = pallet_collective::Instance1;
+impl pallet_collective::Trait<CouncilCollective> for Runtime {
+    type Origin = Origin;
+    type Proposal = Call;
     type Event = Event;
-    type Call = Call;
+    type MotionDuration = CouncilMotionDuration;
+    type MaxProposals = CouncilMaxProposals;
+    type MaxMembers = CouncilMaxMembers;
+    type DefaultVote = pallet_collective::PrimeDefaultVote;
+    type WeightInfo = ();
+}
+
+/// Root or two thirds of the admins council.
+type EnsureRootOrCouncil = frame_system::EnsureOneOf<
+    AccountId,
+    frame_system::EnsureRoot<AccountId>,
+    pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, CouncilCollective>,
+>;
+
+pallet_template::role_set! {
+    /// Roles of the registry accounts. Encoded as `u8`, switching to a wider type to add
+    /// more than 8 roles needs a migration of the stored roles.
+    pub struct AccountRoles: u8 {
+        /// Manages the registry and forms the council.
+        const ADMIN = 0x01;
+        /// Regular registered account.
+        const USER = 0x02;
+    }
 }
 
 // Configure pallet constants
 parameter_types! {
-    pub const AdminRole: u8 = ADMIN_ROLE;
+    /// Accounts are reported as stale in 30 days since creation (milliseconds).
+    pub const StaleAccountAge: u64 = 30 * 24 * 60 * 60 * 1000;
+    pub const StaleReportInterval: BlockNumber = HOURS;
+    pub const TemplateUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+    pub const MaxMetadataLen: u32 = 64;
+    pub const MetadataDepositBase: Balance = 50_000;
+    pub const MetadataDepositPerByte: Balance = 500;
+    pub const RoleRequestDeposit: Balance = 10_000;
+    pub const AdminDeposit: Balance = 100_000;
+    pub const UserDeposit: Balance = 10_000;
+    pub const MaxBatchSize: u32 = 100;
+    pub const MaxConfigLen: u32 = 128;
+    pub const MaxConfigHistory: u32 = 16;
+    /// Admins keep their roles when their balance is dusted.
+    pub const TemplateReapPolicy: ReapPolicy = ReapPolicy::KeepAdmins;
 }
 
 /// Configure the template pallet in pallets/template.
 impl pallet_template::Trait for Runtime {
     type Event = Event;
-    type AdminRole = AdminRole;
-    type AccountRole = u8;
+    type AccountRole = AccountRoles;
     type Currency = Balances;
+    type MaxMetadataLen = MaxMetadataLen;
+    type MetadataDepositBase = MetadataDepositBase;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type RoleRequestDeposit = RoleRequestDeposit;
+    type AdminDeposit = AdminDeposit;
+    type UserDeposit = UserDeposit;
+    type MaxLocks = MaxLocks;
+    type MaxConfigLen = MaxConfigLen;
+    type MaxConfigHistory = MaxConfigHistory;
+    // Council motions manage the registry and dispatch root calls, e.g. runtime upgrades.
+    type AdminOrigin = EnsureRootOrCouncil;
+    type AdminsChanged = Council;
+    type MaxBatchSize = MaxBatchSize;
+    type ReapPolicy = TemplateReapPolicy;
+    type Call = Call;
+    type AuthorityId = pallet_template::crypto::TemplateAuthId;
+    type StaleAccountAge = StaleAccountAge;
+    type ReportInterval = StaleReportInterval;
+    type UnsignedPriority = TemplateUnsignedPriority;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const MinValidators: u32 = 1;
+}
+
+impl pallet_validator_set::Trait for Runtime {
+    type Event = Event;
+    type ManagerOrigin = EnsureAdmin<Runtime>;
+    type MinValidators = MinValidators;
     type WeightInfo = ();
 }
 
@@ -291,12 +443,19 @@ construct_runtime!(
         RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Module, Call, Storage},
         Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
         Aura: pallet_aura::{Module, Config<T>, Inherent},
-        Grandpa: pallet_grandpa::{Module, Call, Storage, Config, Event},
+        Authorship: pallet_authorship::{Module, Call, Storage},
+        Grandpa: pallet_grandpa::{Module, Call, Storage, Config, Event, ValidateUnsigned},
         Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+        // Validators set has to be initialized before the session on genesis.
+        ValidatorSet: pallet_validator_set::{Module, Call, Storage, Config<T>, Event<T>},
+        // Session sets up Aura and Grandpa authorities on genesis.
+        Session: pallet_session::{Module, Call, Storage, Event, Config<T>},
+        Historical: pallet_session_historical::{Module},
+        Offences: pallet_offences::{Module, Call, Storage, Event},
         TransactionPayment: pallet_transaction_payment::{Module, Storage, Config},
-        Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
+        Council: pallet_collective::<Instance1>::{Module, Call, Storage, Origin<T>, Event<T>, Config<T>},
         // Include the custom logic from the template pallet in the runtime.
-        TemplateModule: pallet_template::{Module, Call, Storage, Config<T>, Event<T>},
+        TemplateModule: pallet_template::{Module, Call, Storage, Config<T>, Event<T>, ValidateUnsigned},
     }
 );
 
@@ -318,6 +477,7 @@ pub type SignedExtra = (
     frame_system::CheckEra<Runtime>,
     frame_system::CheckNonce<Runtime>,
     frame_system::CheckWeight<Runtime>,
+    CheckRateLimit<Runtime>,
     pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
@@ -331,8 +491,30 @@ pub type Executive = frame_executive::Executive<
     frame_system::ChainContext<Runtime>,
     Runtime,
     AllModules,
+    SudoToCouncil,
 >;
 
+/// Hand the sudo power over to the council of the registry admins.
+/// Removes `pallet_sudo` storage and sets the council members to the registry admins.
+/// Does nothing on chains without `pallet_sudo` storage.
+pub struct SudoToCouncil;
+
+impl frame_support::traits::OnRuntimeUpgrade for SudoToCouncil {
+    fn on_runtime_upgrade() -> Weight {
+        use frame_support::{storage::unhashed, StorageHasher, Twox128};
+
+        let sudo_prefix = Twox128::hash(b"Sudo");
+        let sudo_key = [sudo_prefix, Twox128::hash(b"Key")].concat();
+        if !unhashed::exists(&sudo_key) {
+            return 0;
+        }
+        unhashed::kill_prefix(&sudo_prefix);
+
+        TemplateModule::initialize_admins()
+            .saturating_add(<Runtime as frame_system::Trait>::DbWeight::get().reads_writes(1, 1))
+    }
+}
+
 pub type TemplateAccount = AccountOf<Runtime>;
 
 impl_runtime_apis! {
@@ -424,23 +606,27 @@ impl_runtime_apis! {
         }
 
         fn submit_report_equivocation_unsigned_extrinsic(
-            _equivocation_proof: fg_primitives::EquivocationProof<
+            equivocation_proof: fg_primitives::EquivocationProof<
                 <Block as BlockT>::Hash,
                 NumberFor<Block>,
             >,
-            _key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
+            key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
         ) -> Option<()> {
-            None
+            let key_owner_proof = key_owner_proof.decode()?;
+
+            Grandpa::submit_unsigned_equivocation_report(
+                equivocation_proof,
+                key_owner_proof,
+            )
         }
 
         fn generate_key_ownership_proof(
             _set_id: fg_primitives::SetId,
-            _authority_id: GrandpaId,
+            authority_id: GrandpaId,
         ) -> Option<fg_primitives::OpaqueKeyOwnershipProof> {
-            // NOTE: this is the only implementation possible since we've
-            // defined our key owner proof type as a bottom type (i.e. a type
-            // with no values).
-            None
+            Historical::prove((fg_primitives::KEY_TYPE, authority_id))
+                .map(|p| p.encode())
+                .map(fg_primitives::OpaqueKeyOwnershipProof::new)
         }
     }
 
@@ -0,0 +1,260 @@
+//! End-to-end tests of the runtime wiring. Genesis is built from the development chain spec
+//! of the node, signed extrinsics are applied through `Executive`.
+
+#[allow(dead_code)]
+#[path = "../../node/src/chain_spec.rs"]
+mod chain_spec;
+
+use codec::Encode;
+use frame_support::{
+    assert_ok,
+    traits::{Currency, OnFinalize},
+    weights::GetDispatchInfo,
+};
+use node_template_runtime::{
+    pallet_template::{self, RateLimit, RATE_LIMIT_EXCEEDED},
+    AccountId, AccountRoles, Balance, Balances, BalancesCall, BuildStorage, Call, Event, Executive,
+    ExtrinsicBaseWeight, Header, Origin, Runtime, SignedExtra, System, TemplateModule,
+    TransactionPayment, UncheckedExtrinsic,
+};
+use pallet_transaction_payment::Multiplier;
+use sp_keyring::AccountKeyring;
+use sp_runtime::{
+    generic::{Era, SignedPayload},
+    traits::Header as _,
+    transaction_validity::{InvalidTransaction, TransactionValidityError},
+    DispatchError, FixedPointNumber,
+};
+
+// Genesis of the development chain with the first block initialized
+fn new_test_ext() -> sp_io::TestExternalities {
+    let storage = chain_spec::development_config()
+        .expect("development chain spec")
+        .build_storage()
+        .expect("development genesis");
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.execute_with(|| {
+        Executive::initialize_block(&Header::new(
+            1,
+            Default::default(),
+            Default::default(),
+            System::block_hash(0),
+            Default::default(),
+        ))
+    });
+    ext
+}
+
+// signed extrinsic with the next nonce of the signer
+fn sign(signer: AccountKeyring, call: Call) -> UncheckedExtrinsic {
+    let who = signer.to_account_id();
+    let extra: SignedExtra = (
+        frame_system::CheckSpecVersion::new(),
+        frame_system::CheckTxVersion::new(),
+        frame_system::CheckGenesis::new(),
+        frame_system::CheckEra::from(Era::Immortal),
+        frame_system::CheckNonce::from(System::account_nonce(&who)),
+        frame_system::CheckWeight::new(),
+        pallet_template::CheckRateLimit::new(),
+        pallet_transaction_payment::ChargeTransactionPayment::from(0),
+    );
+    let payload = SignedPayload::new(call, extra).expect("valid additional signed data");
+    let signature = payload.using_encoded(|payload| signer.sign(payload));
+    let (call, extra, _) = payload.deconstruct();
+    UncheckedExtrinsic::new_signed(call, who, signature.into(), extra)
+}
+
+// fee of the extrinsic with the identity fee multiplier
+fn expected_fee(xt: &UncheckedExtrinsic) -> Balance {
+    let info = xt.get_dispatch_info();
+    let len = xt.encode().len() as Balance;
+    ExtrinsicBaseWeight::get() as Balance + len + info.weight as Balance
+}
+
+fn events() -> Vec<Event> {
+    System::events().into_iter().map(|evt| evt.event).collect()
+}
+
+#[test]
+fn it_build_development_genesis() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let bob = AccountKeyring::Bob.to_account_id();
+
+        assert!(TemplateModule::account_is_admin(&alice));
+        assert!(!TemplateModule::account_registry(&bob).is_enable());
+        assert_eq!(TemplateModule::admins(), vec![alice.clone()]);
+        assert_eq!(
+            pallet_collective::Module::<Runtime, pallet_collective::Instance1>::members(),
+            vec![alice]
+        );
+        assert_eq!(
+            TemplateModule::rate_limits(AccountRoles::empty()),
+            Some(RateLimit {
+                calls: 10,
+                period: 100,
+            })
+        );
+        assert_eq!(Balances::free_balance(&bob), 1 << 60);
+    });
+}
+
+#[test]
+fn it_charge_fee_by_weight_and_length() {
+    new_test_ext().execute_with(|| {
+        let bob = AccountKeyring::Bob.to_account_id();
+        let charlie = AccountKeyring::Charlie.to_account_id();
+
+        let xt = sign(
+            AccountKeyring::Bob,
+            Call::Balances(BalancesCall::transfer(charlie.clone(), 1000)),
+        );
+        let fee = expected_fee(&xt);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        assert_eq!(Balances::free_balance(&bob), (1 << 60) - 1000 - fee);
+        assert_eq!(Balances::free_balance(&charlie), 1000);
+        assert!(events().contains(&Event::pallet_balances(
+            pallet_balances::RawEvent::Transfer(bob, charlie, 1000)
+        )));
+
+        // `IdentityMultiplierUpdater` keeps the weight part of the fee
+        TransactionPayment::on_finalize(1);
+        assert_eq!(
+            TransactionPayment::next_fee_multiplier(),
+            Multiplier::saturating_from_integer(1)
+        );
+    });
+}
+
+#[test]
+fn it_rate_limit_free_calls() {
+    new_test_ext().execute_with(|| {
+        // User without balance changes `something` by free calls
+        let dave = AccountKeyring::Dave.to_account_id();
+        let xt = sign(
+            AccountKeyring::Alice,
+            Call::TemplateModule(pallet_template::Call::account_add(
+                dave.clone(),
+                AccountRoles::USER,
+            )),
+        );
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        assert_ok!(TemplateModule::set_rate_limit(
+            Origin::root(),
+            AccountRoles::USER,
+            Some(RateLimit {
+                calls: 10,
+                period: 100,
+            })
+        ));
+
+        for something in 0..10 {
+            let xt = sign(
+                AccountKeyring::Dave,
+                Call::TemplateModule(pallet_template::Call::do_something(something)),
+            );
+            assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        }
+        assert_eq!(TemplateModule::something(), Some(9));
+        assert!(events().contains(&Event::pallet_template(
+            pallet_template::RawEvent::SomethingStored(9, dave.clone())
+        )));
+        assert_eq!(Balances::total_balance(&dave), 0);
+
+        let xt = sign(
+            AccountKeyring::Dave,
+            Call::TemplateModule(pallet_template::Call::do_something(10)),
+        );
+        assert_eq!(
+            Executive::apply_extrinsic(xt),
+            Err(TransactionValidityError::Invalid(
+                InvalidTransaction::Custom(RATE_LIMIT_EXCEEDED)
+            ))
+        );
+        assert_eq!(TemplateModule::something(), Some(9));
+    });
+}
+
+#[test]
+fn it_lock_transferred_balance_except_fee() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let charlie = AccountKeyring::Charlie.to_account_id();
+
+        let xt = sign(
+            AccountKeyring::Alice,
+            Call::TemplateModule(pallet_template::Call::account_transfer_and_lock(
+                charlie.clone(),
+                1_000_000_000_000,
+            )),
+        );
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        let events = events();
+        assert!(events.contains(&Event::pallet_balances(
+            pallet_balances::RawEvent::Transfer(alice.clone(), charlie.clone(), 1_000_000_000_000)
+        )));
+        assert!(events.contains(&Event::pallet_template(
+            pallet_template::RawEvent::BalanceLocked(charlie.clone(), 1_000_000_000_000)
+        )));
+
+        // Locked balance pays the fee but can't be transferred
+        let xt = sign(
+            AccountKeyring::Charlie,
+            Call::Balances(BalancesCall::transfer(alice, 1000)),
+        );
+        let fee = expected_fee(&xt);
+        assert_eq!(
+            Executive::apply_extrinsic(xt),
+            Ok(Err(DispatchError::from(
+                pallet_balances::Error::<Runtime, _>::LiquidityRestrictions
+            )))
+        );
+        assert_eq!(Balances::free_balance(&charlie), 1_000_000_000_000 - fee);
+    });
+}
+
+#[test]
+fn it_remove_reaped_account_from_registry() {
+    new_test_ext().execute_with(|| {
+        let bob = AccountKeyring::Bob.to_account_id();
+        let charlie: AccountId = AccountKeyring::Charlie.to_account_id();
+        let balance: Balance = 1_000_000_000_000;
+
+        let xt = sign(
+            AccountKeyring::Alice,
+            Call::Balances(BalancesCall::transfer(charlie.clone(), balance)),
+        );
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        let xt = sign(
+            AccountKeyring::Alice,
+            Call::TemplateModule(pallet_template::Call::account_add(
+                charlie.clone(),
+                AccountRoles::USER,
+            )),
+        );
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        assert!(TemplateModule::account_registry(&charlie).is_enable());
+
+        // Leave less than the existential deposit after the fee
+        let probe = sign(
+            AccountKeyring::Charlie,
+            Call::Balances(BalancesCall::transfer(bob.clone(), balance)),
+        );
+        let fee = expected_fee(&probe);
+        let xt = sign(
+            AccountKeyring::Charlie,
+            Call::Balances(BalancesCall::transfer(bob, balance - fee - 1)),
+        );
+        assert_eq!(xt.encode().len(), probe.encode().len());
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        assert_eq!(Balances::total_balance(&charlie), 0);
+        assert!(
+            events().contains(&Event::frame_system(frame_system::RawEvent::KilledAccount(
+                charlie.clone()
+            )))
+        );
+        assert!(!TemplateModule::account_registry(&charlie).is_enable());
+    });
+}